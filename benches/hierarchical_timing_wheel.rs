@@ -1,5 +1,6 @@
-use criterion::{Criterion, criterion_group, criterion_main};
+use criterion::{BatchSize, BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
 use std::hint::black_box;
+use timing_wheel::WheelBuilder;
 
 fn bench_schedule_slot_overload(c: &mut Criterion) {
     let delay = 100;
@@ -27,5 +28,184 @@ fn bench_schedule_slot_overload(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, bench_schedule_slot_overload);
+fn bench_tick_by_levels(c: &mut Criterion) {
+    const SLOTS_PER_LEVEL: usize = 4;
+    const TIMERS: usize = 4096;
+    const STEPS: usize = 1024;
+
+    let mut group = c.benchmark_group("tick_by_levels");
+    group.throughput(Throughput::Elements(STEPS as u64));
+    for levels in 1..=8u32 {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(levels),
+            &levels,
+            |b, &levels| {
+                b.iter_batched(
+                    || {
+                        let mut wheel = timing_wheel::hierarchical(levels, 16, SLOTS_PER_LEVEL);
+                        let max_delay = wheel.max_delay();
+                        for i in 0..TIMERS {
+                            wheel.schedule(i * max_delay / TIMERS + 1, i).unwrap();
+                        }
+                        wheel
+                    },
+                    |mut wheel| black_box(wheel.tick(STEPS)),
+                    BatchSize::SmallInput,
+                );
+            },
+        );
+    }
+    group.finish();
+}
+
+fn bench_schedule_by_slots(c: &mut Criterion) {
+    const RANGE: usize = 1 << 16;
+    const TIMERS: usize = 4096;
+
+    let mut group = c.benchmark_group("schedule_by_slots");
+    group.throughput(Throughput::Elements(TIMERS as u64));
+    for shift in 2..=8u32 {
+        let slots_per_level = 1usize << shift;
+        // just enough levels to cover RANGE, so wider rings need fewer of them
+        let levels = 16u32.div_ceil(shift);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(slots_per_level),
+            &slots_per_level,
+            |b, &slots_per_level| {
+                b.iter_batched(
+                    || timing_wheel::hierarchical(levels, 16, slots_per_level),
+                    |mut wheel| {
+                        for i in 0..TIMERS {
+                            let delay = i.wrapping_mul(40503) % (RANGE - 1) + 1;
+                            black_box(wheel.schedule(delay, i).unwrap());
+                        }
+                        wheel
+                    },
+                    BatchSize::SmallInput,
+                );
+            },
+        );
+    }
+    group.finish();
+}
+
+fn bench_tick_single_step(c: &mut Criterion) {
+    const STEPS: usize = 1024;
+
+    let wheel = || {
+        let mut wheel = timing_wheel::hierarchical(3, 16, 64);
+        for i in 0..STEPS {
+            wheel.schedule(i * 7 % STEPS + 1, i).unwrap();
+        }
+        wheel
+    };
+    let mut group = c.benchmark_group("single_step");
+    group.throughput(Throughput::Elements(STEPS as u64));
+    group.bench_function("tick(1)", |b| {
+        b.iter_batched(
+            wheel,
+            |mut wheel| {
+                for _ in 0..STEPS {
+                    black_box(wheel.tick(1));
+                }
+            },
+            BatchSize::SmallInput,
+        );
+    });
+    group.bench_function("tick_single_step", |b| {
+        b.iter_batched(
+            wheel,
+            |mut wheel| {
+                for _ in 0..STEPS {
+                    black_box(wheel.tick_single_step());
+                }
+            },
+            BatchSize::SmallInput,
+        );
+    });
+    group.finish();
+}
+
+fn bench_cascade_mode(c: &mut Criterion) {
+    const TIMERS: usize = 4096;
+
+    // four levels of eight slots: most timers start in levels 2 and 3 and cascade on the way down
+    let wheel = |builder: WheelBuilder<usize>| {
+        let mut wheel = builder.build();
+        let max_delay = wheel.max_delay();
+        for i in 0..TIMERS {
+            wheel
+                .schedule(64 + i * (max_delay - 64) / TIMERS, i)
+                .unwrap();
+        }
+        wheel
+    };
+    let mut group = c.benchmark_group("cascade_mode");
+    group.throughput(Throughput::Elements(TIMERS as u64));
+    group.bench_function("eager", |b| {
+        b.iter_batched(
+            || wheel(WheelBuilder::new(4, 16, 8)),
+            |mut wheel| black_box(wheel.tick(wheel.max_delay())),
+            BatchSize::SmallInput,
+        );
+    });
+    group.bench_function("lazy", |b| {
+        b.iter_batched(
+            || wheel(WheelBuilder::new(4, 16, 8).with_lazy_cascade()),
+            |mut wheel| black_box(wheel.tick(wheel.max_delay())),
+            BatchSize::SmallInput,
+        );
+    });
+    group.finish();
+}
+
+fn bench_cancel(c: &mut Criterion) {
+    const TIMERS: usize = 4096;
+
+    let mut group = c.benchmark_group("cancel");
+    group.throughput(Throughput::Elements(TIMERS as u64));
+    group.bench_function("spread", |b| {
+        b.iter_batched(
+            || {
+                let mut wheel = timing_wheel::hierarchical(3, 16, 64);
+                let max_delay = wheel.max_delay();
+                let handles: Vec<_> = (0..TIMERS)
+                    .map(|i| wheel.schedule_with_handle(i.wrapping_mul(40503) % max_delay + 1, i))
+                    .collect::<Result<_, _>>()
+                    .unwrap();
+                (wheel, handles)
+            },
+            |(mut wheel, handles)| {
+                for handle in handles.into_iter().rev() {
+                    black_box(wheel.cancel(handle));
+                }
+            },
+            BatchSize::SmallInput,
+        );
+    });
+    group.finish();
+}
+
+fn bench_len(c: &mut Criterion) {
+    // 16 levels of 4 slots: summing the slots would walk all 64 of them on every call
+    let mut wheel = timing_wheel::hierarchical(16, 16, 4);
+    let max_delay = wheel.max_delay();
+    for i in 0..4096usize {
+        wheel
+            .schedule(i.wrapping_mul(40503) % max_delay + 1, i)
+            .unwrap();
+    }
+    c.bench_function("len", |b| b.iter(|| black_box(&wheel).len()));
+}
+
+criterion_group!(
+    benches,
+    bench_schedule_slot_overload,
+    bench_tick_by_levels,
+    bench_schedule_by_slots,
+    bench_tick_single_step,
+    bench_cascade_mode,
+    bench_cancel,
+    bench_len
+);
 criterion_main!(benches);
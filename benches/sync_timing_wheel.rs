@@ -0,0 +1,64 @@
+use criterion::{Criterion, criterion_group, criterion_main};
+use std::hint::black_box;
+use std::sync::Arc;
+use std::thread;
+
+use timing_wheel::{PartitionedTimingWheel, ShardedTimingWheel, SyncTimingWheel, hierarchical};
+
+const PRODUCERS: usize = 8;
+const TIMERS_PER_PRODUCER: usize = 10_000;
+
+fn produce(schedule: impl Fn(usize, usize) + Send + Sync + 'static) {
+    let schedule = Arc::new(schedule);
+    let producers: Vec<_> = (0..PRODUCERS)
+        .map(|_| {
+            let schedule = Arc::clone(&schedule);
+            thread::spawn(move || {
+                for i in 0..TIMERS_PER_PRODUCER {
+                    schedule(i % 999 + 1, i);
+                }
+            })
+        })
+        .collect();
+    for producer in producers {
+        producer.join().unwrap();
+    }
+}
+
+fn bench_concurrent_schedule(c: &mut Criterion) {
+    c.bench_function("sync_schedule_contended", |b| {
+        b.iter(|| {
+            let wheel = Arc::new(SyncTimingWheel::new(hierarchical(3, 64, 10)));
+            let producer = Arc::clone(&wheel);
+            produce(move |delay, i| {
+                black_box(producer.schedule(delay, i).unwrap());
+            });
+            black_box(wheel.tick(999));
+        });
+    });
+
+    c.bench_function("sharded_schedule_contended", |b| {
+        b.iter(|| {
+            let wheel = Arc::new(ShardedTimingWheel::new(PRODUCERS, 3, 64, 10));
+            let producer = Arc::clone(&wheel);
+            produce(move |delay, i| {
+                black_box(producer.schedule(delay, i).unwrap());
+            });
+            black_box(wheel.tick(999));
+        });
+    });
+
+    c.bench_function("partitioned_schedule_contended", |b| {
+        b.iter(|| {
+            let wheel = Arc::new(PartitionedTimingWheel::new(PRODUCERS, 3, 64, 10));
+            let producer = Arc::clone(&wheel);
+            produce(move |delay, i| {
+                black_box(producer.schedule(delay, i).unwrap());
+            });
+            black_box(wheel.tick(999));
+        });
+    });
+}
+
+criterion_group!(benches, bench_concurrent_schedule);
+criterion_main!(benches);
@@ -0,0 +1,40 @@
+use criterion::{Criterion, criterion_group, criterion_main};
+use std::hint::black_box;
+
+// one timer every fourth tick, so most ticks fire nothing
+fn sparse_wheel() -> timing_wheel::HierarchicalTimingWheel<usize> {
+    let mut wheel = timing_wheel::hierarchical(3, 4, 16);
+    for i in 0..1024usize {
+        wheel.schedule(i * 4 % 4000 + 1, i).unwrap();
+    }
+    wheel
+}
+
+fn bench_sparse_ticks(c: &mut Criterion) {
+    c.bench_function("tick_sparse", |b| {
+        b.iter_batched(
+            sparse_wheel,
+            |mut wheel| {
+                for _ in 0..4096 {
+                    black_box(wheel.tick(1));
+                }
+            },
+            criterion::BatchSize::SmallInput,
+        );
+    });
+
+    c.bench_function("tick_small_sparse", |b| {
+        b.iter_batched(
+            sparse_wheel,
+            |mut wheel| {
+                for _ in 0..4096 {
+                    black_box(wheel.tick_small::<4>(1));
+                }
+            },
+            criterion::BatchSize::SmallInput,
+        );
+    });
+}
+
+criterion_group!(benches, bench_sparse_ticks);
+criterion_main!(benches);
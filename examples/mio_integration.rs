@@ -0,0 +1,57 @@
+//! Drives a `WallClockWheel` from a `mio` event loop.
+//!
+//! mio has no timer source, so the wheel's next expiry becomes the `poll` timeout: the loop
+//! sleeps until either an I/O event arrives or the earliest timer is due, then polls the wheel.
+//! Here the only I/O source is a `Waker` that a background thread uses to simulate activity.
+
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use mio::{Events, Poll, Token, Waker};
+use timing_wheel::{WallClockWheel, hierarchical};
+
+const WAKER: Token = Token(0);
+const RESOLUTION: Duration = Duration::from_millis(10);
+
+fn main() -> std::io::Result<()> {
+    let mut poll = Poll::new()?;
+    let waker = Arc::new(Waker::new(poll.registry(), WAKER)?);
+    let mut events = Events::with_capacity(16);
+
+    // per-connection idle timeouts, keyed by the connection's token
+    let mut wheel = WallClockWheel::new(hierarchical::<Token>(3, 64, 64), RESOLUTION);
+    let start = Instant::now();
+    for (n, after) in [(1, 30), (2, 120), (3, 250)] {
+        let deadline = start + Duration::from_millis(after);
+        wheel.schedule_deadline(deadline, Token(n)).unwrap();
+    }
+
+    let activity = Arc::clone(&waker);
+    thread::spawn(move || {
+        thread::sleep(Duration::from_millis(60));
+        activity.wake().unwrap();
+    });
+
+    while !wheel.wheel().is_empty() {
+        let timeout = wheel
+            .wheel()
+            .next_expiry()
+            .map(|ticks| RESOLUTION * ticks as u32);
+        poll.poll(&mut events, timeout)?;
+
+        for event in events.iter() {
+            if event.token() == WAKER {
+                println!("{:>4?} woken by I/O", start.elapsed().as_millis());
+            }
+        }
+        for token in wheel.poll_now() {
+            println!(
+                "{:>4?} connection {} timed out",
+                start.elapsed().as_millis(),
+                token.0
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,111 @@
+use crate::{HierarchicalTimingWheel, ScheduleError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryInfo {
+    pub original_delay: usize,
+    pub current_delay: usize,
+    pub retry_count: u8,
+}
+
+struct Backoff<T> {
+    original_delay: usize,
+    current_delay: usize,
+    max_retries: u8,
+    retry_count: u8,
+    timer: T,
+}
+
+/// Wheel that re-schedules every fired timer at twice its previous delay until it runs out of
+/// retries or the doubled delay would exceed `max_delay`, and only then hands it back.
+pub struct BackoffWheel<T> {
+    wheel: HierarchicalTimingWheel<Backoff<T>>,
+    max_delay: usize,
+}
+
+impl<T> BackoffWheel<T> {
+    pub fn new(
+        levels: u32,
+        slot_capacity: usize,
+        slots_per_level: usize,
+        max_delay: usize,
+    ) -> BackoffWheel<T> {
+        let wheel = HierarchicalTimingWheel::new(levels, slot_capacity, slots_per_level);
+        let max_delay = max_delay.min(wheel.max_delay());
+        Self { wheel, max_delay }
+    }
+
+    pub fn schedule(
+        &mut self,
+        delay_ticks: usize,
+        max_retries: u8,
+        timer: T,
+    ) -> Result<(usize, usize), ScheduleError> {
+        let backoff = Backoff {
+            original_delay: delay_ticks,
+            current_delay: delay_ticks.max(1),
+            max_retries,
+            retry_count: 0,
+            timer,
+        };
+        self.wheel.schedule(delay_ticks, backoff)
+    }
+
+    pub fn tick(&mut self, steps: usize) -> Vec<(T, RetryInfo)> {
+        let mut due = Vec::new();
+        for _ in 0..steps {
+            for mut backoff in self.wheel.tick(1) {
+                let next_delay = backoff.current_delay.saturating_mul(2);
+                if backoff.retry_count < backoff.max_retries && next_delay <= self.max_delay {
+                    backoff.current_delay = next_delay;
+                    backoff.retry_count += 1;
+                    self.wheel
+                        .schedule(next_delay, backoff)
+                        .expect("backoff delay is bounded by the wheel's max delay");
+                } else {
+                    let info = RetryInfo {
+                        original_delay: backoff.original_delay,
+                        current_delay: backoff.current_delay,
+                        retry_count: backoff.retry_count,
+                    };
+                    due.push((backoff.timer, info));
+                }
+            }
+        }
+        due
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_doubles_delay_until_retries_exhausted() {
+        let mut wheel = BackoffWheel::new(2, 16, 10, 99);
+        wheel.schedule(1, 3, "A").unwrap();
+
+        // fires at 1, then re-fires after 2, 4 and 8 more ticks
+        assert!(wheel.tick(14).is_empty());
+        let info = RetryInfo {
+            original_delay: 1,
+            current_delay: 8,
+            retry_count: 3,
+        };
+        assert_eq!(wheel.tick(1), vec![("A", info)]);
+        assert!(wheel.tick(100).is_empty());
+    }
+
+    #[test]
+    fn test_stops_doubling_at_max_delay() {
+        let mut wheel = BackoffWheel::new(3, 16, 10, 30);
+        wheel.schedule(10, 5, "B").unwrap();
+
+        assert!(wheel.tick(29).is_empty());
+        let info = RetryInfo {
+            original_delay: 10,
+            current_delay: 20,
+            retry_count: 1,
+        };
+        assert_eq!(wheel.tick(1), vec![("B", info)]);
+    }
+}
@@ -0,0 +1,81 @@
+use std::marker::PhantomData;
+
+use crate::{HierarchicalTimingWheel, ScheduleError, TimerHandle};
+
+/// Invariant in `'w`, so one brand can never be coerced into another.
+type Brand<'w> = PhantomData<fn(&'w ()) -> &'w ()>;
+
+/// A `TimerHandle` branded with the lifetime of the `with_brand` call that issued it. Every call
+/// gets a fresh `'w`, so passing a handle to another wheel's `BrandedWheel`, or keeping it past
+/// the closure, is a compile error rather than the runtime panic `TimerHandle` gives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BrandedHandle<'w> {
+    handle: TimerHandle,
+    _brand: Brand<'w>,
+}
+
+impl BrandedHandle<'_> {
+    pub fn handle(&self) -> TimerHandle {
+        self.handle
+    }
+}
+
+/// The wheel borrowed by `with_brand`, only accepting handles it issued itself.
+pub struct BrandedWheel<'w, 'a, T> {
+    wheel: &'a mut HierarchicalTimingWheel<T>,
+    _brand: Brand<'w>,
+}
+
+impl<'w, T> BrandedWheel<'w, '_, T> {
+    pub fn schedule(
+        &mut self,
+        delay_ticks: usize,
+        timer: T,
+    ) -> Result<BrandedHandle<'w>, ScheduleError> {
+        let handle = self.wheel.schedule_with_handle(delay_ticks, timer)?;
+        Ok(BrandedHandle {
+            handle,
+            _brand: PhantomData,
+        })
+    }
+
+    pub fn cancel(&mut self, handle: BrandedHandle<'w>) -> Option<T> {
+        self.wheel.cancel(handle.handle)
+    }
+
+    pub fn scheduled_at(&self, handle: BrandedHandle<'w>) -> Option<u64> {
+        self.wheel.scheduled_at(handle.handle)
+    }
+
+    pub fn wheel(&mut self) -> &mut HierarchicalTimingWheel<T> {
+        self.wheel
+    }
+}
+
+impl<T> HierarchicalTimingWheel<T> {
+    /// Runs `f` with a brand unique to this call; see `BrandedHandle`.
+    pub fn with_brand<R>(&mut self, f: impl for<'w> FnOnce(BrandedWheel<'w, '_, T>) -> R) -> R {
+        f(BrandedWheel {
+            wheel: self,
+            _brand: PhantomData,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_branded_schedule_and_cancel() {
+        let mut wheel = HierarchicalTimingWheel::new(2, 16, 10);
+        let cancelled = wheel.with_brand(|mut branded| {
+            let a = branded.schedule(5, "A").unwrap();
+            branded.schedule(5, "B").unwrap();
+            assert_eq!(branded.scheduled_at(a), Some(5));
+            branded.cancel(a)
+        });
+        assert_eq!(cancelled, Some("A"));
+        assert_eq!(wheel.tick(5), vec!["B"]);
+    }
+}
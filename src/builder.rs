@@ -0,0 +1,347 @@
+use std::fmt;
+use std::sync::Arc;
+
+use crate::HierarchicalTimingWheel;
+
+/// What `schedule` does with a delay beyond `max_delay`.
+pub enum OverflowPolicy<T> {
+    /// Reject the timer with `ScheduleError::DelayTooLarge`.
+    Error,
+    /// Schedule the timer at `max_delay`.
+    Clamp,
+    /// Schedule the timer at `delay % max_delay`.
+    Wrap,
+    /// Hand the delay and the timer to the callback. `schedule` still returns
+    /// `ScheduleError::DelayTooLarge`, as the timer was not placed on the wheel.
+    Custom(Arc<dyn Fn(usize, T) + Send + Sync>),
+}
+
+impl<T> Clone for OverflowPolicy<T> {
+    fn clone(&self) -> Self {
+        match self {
+            OverflowPolicy::Error => OverflowPolicy::Error,
+            OverflowPolicy::Clamp => OverflowPolicy::Clamp,
+            OverflowPolicy::Wrap => OverflowPolicy::Wrap,
+            OverflowPolicy::Custom(handler) => OverflowPolicy::Custom(Arc::clone(handler)),
+        }
+    }
+}
+
+impl<T> fmt::Debug for OverflowPolicy<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OverflowPolicy::Error => f.write_str("Error"),
+            OverflowPolicy::Clamp => f.write_str("Clamp"),
+            OverflowPolicy::Wrap => f.write_str("Wrap"),
+            OverflowPolicy::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+/// Order in which timers are kept within a slot, as seen by `peek_at_slot`. `tick` fires in
+/// schedule order either way.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OrderingMode {
+    /// In the order they were scheduled.
+    #[default]
+    Fifo,
+    /// Unspecified; cancelling a timer may reorder the rest of its slot.
+    Unordered,
+}
+
+/// How timers graduating from an upper ring move down the wheel.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CascadeMode {
+    /// Re-placed by their exact remainder, possibly cascading again, so they fire on time.
+    #[default]
+    Eager,
+    /// Rounded up to the next slot of the ring directly below and fired when that slot comes
+    /// due, so each timer cascades once. Timers from level 2 and up fire up to one slot of the
+    /// ring below late.
+    Lazy,
+}
+
+pub struct WheelBuilder<T> {
+    levels: u32,
+    slot_capacity: usize,
+    slots_per_level: usize,
+    overflow_policy: OverflowPolicy<T>,
+    name: Option<String>,
+    ordering_mode: OrderingMode,
+    cascade_mode: CascadeMode,
+    max_cascade_per_tick: Option<usize>,
+    min_delay_ticks: usize,
+}
+
+impl<T> WheelBuilder<T> {
+    pub fn new(levels: u32, slot_capacity: usize, slots_per_level: usize) -> WheelBuilder<T> {
+        Self {
+            levels,
+            slot_capacity,
+            slots_per_level,
+            overflow_policy: OverflowPolicy::Error,
+            name: None,
+            ordering_mode: OrderingMode::Fifo,
+            cascade_mode: CascadeMode::Eager,
+            max_cascade_per_tick: None,
+            min_delay_ticks: 0,
+        }
+    }
+
+    pub fn with_overflow_policy(mut self, policy: OverflowPolicy<T>) -> WheelBuilder<T> {
+        self.overflow_policy = policy;
+        self
+    }
+
+    /// Name shown in the wheel's `Display` and `Debug` output and in its panic messages.
+    pub fn with_name(mut self, name: impl Into<String>) -> WheelBuilder<T> {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Lets `cancel` swap the last timer of a slot into the cancelled one's place instead of
+    /// shifting the slot, giving up FIFO order within slots. Firing order is unaffected.
+    pub fn with_unordered_cancel(mut self) -> WheelBuilder<T> {
+        self.ordering_mode = OrderingMode::Unordered;
+        self
+    }
+
+    /// Trades accuracy for fewer cascades, see `CascadeMode::Lazy`.
+    pub fn with_lazy_cascade(mut self) -> WheelBuilder<T> {
+        self.cascade_mode = CascadeMode::Lazy;
+        self
+    }
+
+    /// Re-places at most `max` graduating timers per tick, leaving the rest in the slot that came
+    /// due for later ticks so a crowded upper slot does not stall a single tick. Deferred timers
+    /// still fire on time. Overrides `with_lazy_cascade`.
+    pub fn with_max_cascade_per_tick(mut self, max: usize) -> WheelBuilder<T> {
+        self.max_cascade_per_tick = Some(max);
+        self
+    }
+
+    /// Raises every delay below `ticks` to `ticks`, after the overflow policy is applied, so
+    /// bursts of near-immediate timers share one slot.
+    pub fn with_min_delay_ticks(mut self, ticks: usize) -> WheelBuilder<T> {
+        self.min_delay_ticks = ticks;
+        self
+    }
+
+    pub fn build(self) -> HierarchicalTimingWheel<T> {
+        let mut wheel =
+            HierarchicalTimingWheel::new(self.levels, self.slot_capacity, self.slots_per_level);
+        wheel.overflow_policy = self.overflow_policy;
+        wheel.name = self.name;
+        wheel.ordering_mode = self.ordering_mode;
+        wheel.cascade_mode = self.cascade_mode;
+        wheel.max_cascade_per_tick = self.max_cascade_per_tick;
+        wheel.min_delay_ticks = self.min_delay_ticks;
+        wheel
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ScheduleError;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_overflow_error() {
+        let mut wheel = WheelBuilder::new(2, 16, 10)
+            .with_overflow_policy(OverflowPolicy::Error)
+            .build();
+        assert!(matches!(
+            wheel.schedule(100, "A"),
+            Err(ScheduleError::DelayTooLarge)
+        ));
+    }
+
+    #[test]
+    fn test_overflow_clamp() {
+        let mut wheel = WheelBuilder::new(2, 16, 10)
+            .with_overflow_policy(OverflowPolicy::Clamp)
+            .build();
+        assert_eq!(wheel.schedule(1000, "A").unwrap(), (1, 9));
+        assert!(wheel.tick(98).is_empty());
+        assert_eq!(wheel.tick(1), vec!["A"]);
+    }
+
+    #[test]
+    fn test_overflow_wrap() {
+        let mut wheel = WheelBuilder::new(2, 16, 10)
+            .with_overflow_policy(OverflowPolicy::Wrap)
+            .build();
+        assert_eq!(wheel.schedule(150, "A").unwrap(), (1, 5));
+        assert_eq!(wheel.schedule(198, "B").unwrap(), (0, 1));
+        assert_eq!(wheel.tick(1), vec!["B"]);
+        assert!(wheel.tick(49).is_empty());
+        assert_eq!(
+            wheel.schedule_all_or_nothing(vec![(500, "C")]).unwrap(),
+            vec![(0, 5)]
+        );
+    }
+
+    #[test]
+    fn test_overflow_custom() {
+        let overflowed = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&overflowed);
+        let mut wheel = WheelBuilder::new(2, 16, 10)
+            .with_overflow_policy(OverflowPolicy::Custom(Arc::new(move |delay, timer| {
+                sink.lock().unwrap().push((delay, timer))
+            })))
+            .build();
+
+        assert!(wheel.schedule(100, "A").is_err());
+        assert!(wheel.schedule(99, "B").is_ok());
+        assert_eq!(*overflowed.lock().unwrap(), vec![(100, "A")]);
+    }
+
+    #[test]
+    fn test_with_name() {
+        let mut wheel = WheelBuilder::new(2, 16, 10).with_name("conn-7").build();
+        wheel.schedule(5, "A").unwrap();
+        assert_eq!(wheel.name(), Some("conn-7"));
+        assert_eq!(wheel.to_string(), "wheel `conn-7`");
+        assert!(format!("{wheel:?}").contains("name: Some(\"conn-7\")"));
+
+        let unnamed = WheelBuilder::<()>::new(2, 16, 10).build();
+        assert_eq!(unnamed.name(), None);
+        assert!(unnamed.to_string().starts_with("wheel #"));
+    }
+
+    #[test]
+    fn test_unordered_cancel() {
+        let wheel = WheelBuilder::<()>::new(2, 16, 10).build();
+        assert_eq!(wheel.ordering_mode(), OrderingMode::Fifo);
+
+        let mut wheel = WheelBuilder::new(2, 16, 10).with_unordered_cancel().build();
+        assert_eq!(wheel.ordering_mode(), OrderingMode::Unordered);
+        let a = wheel.schedule_with_handle(5, "A").unwrap();
+        wheel.schedule(5, "B").unwrap();
+        wheel.schedule(5, "C").unwrap();
+        assert_eq!(wheel.cancel(a), Some("A"));
+        assert_eq!(wheel.peek_at_slot(0, 5), Some(vec![&"C", &"B"]));
+        assert_eq!(wheel.tick(5), vec!["B", "C"]);
+    }
+
+    #[test]
+    fn test_lazy_cascade() {
+        let mut eager = WheelBuilder::new(3, 16, 10).build();
+        let mut lazy = WheelBuilder::new(3, 16, 10).with_lazy_cascade().build();
+        assert_eq!(lazy.cascade_mode(), CascadeMode::Lazy);
+        for wheel in [&mut eager, &mut lazy] {
+            for delay in [15, 123, 250, 299] {
+                wheel.schedule(delay, delay).unwrap();
+            }
+        }
+
+        let fired_at = |wheel: &mut HierarchicalTimingWheel<usize>| {
+            let batches = wheel.tick_partitioned(300);
+            batches.map(|(tick, due)| (due, tick)).collect::<Vec<_>>()
+        };
+        assert_eq!(
+            fired_at(&mut eager),
+            [
+                (vec![15], 15),
+                (vec![123], 123),
+                (vec![250], 250),
+                (vec![299], 299)
+            ]
+        );
+        // level 1 timers are exact, level 2 ones round up to the next level 1 slot
+        assert_eq!(
+            fired_at(&mut lazy),
+            [
+                (vec![15], 15),
+                (vec![123], 130),
+                (vec![250], 250),
+                (vec![299], 300)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_max_cascade_per_tick() {
+        let mut bounded = WheelBuilder::new(3, 16, 10)
+            .with_max_cascade_per_tick(10)
+            .build();
+        let mut eager = WheelBuilder::new(3, 16, 10).build();
+        let cancelled = bounded.schedule_with_handle(199, 199).unwrap();
+        let eager_cancelled = eager.schedule_with_handle(199, 199).unwrap();
+        // 250 timers crowd the level 2 slot covering ticks 100 to 199
+        for wheel in [&mut bounded, &mut eager] {
+            for i in 0..250 {
+                wheel.schedule(100 + i * 2 % 100, i).unwrap();
+            }
+        }
+
+        bounded.tick(99);
+        let (due, diagnostics) = bounded.tick_with_diagnostics(1);
+        assert_eq!(due.len(), 5);
+        assert_eq!(diagnostics.graduations, 251);
+        assert_eq!(diagnostics.reschedules, 10);
+        assert_eq!(bounded.len(), 246);
+        assert_eq!(bounded.next_expiry(), Some(2));
+        assert_eq!(bounded.cancel(cancelled), Some(199));
+        bounded.check_invariants();
+
+        eager.tick(100);
+        eager.cancel(eager_cancelled);
+        loop {
+            let (due, diagnostics) = bounded.tick_with_diagnostics(1);
+            assert!(diagnostics.reschedules <= 10);
+            assert_eq!(due, eager.tick(1));
+            if bounded.is_empty() {
+                break;
+            }
+        }
+        assert_eq!(bounded.now(), 198);
+    }
+
+    #[test]
+    fn test_zero_cascade_budget_keeps_timers_reachable() {
+        let mut wheel = WheelBuilder::new(2, 16, 10)
+            .with_max_cascade_per_tick(0)
+            .with_unordered_cancel()
+            .build();
+        let e = wheel.schedule_with_handle(11, "E").unwrap();
+        let a = wheel.schedule_with_handle(15, "A").unwrap();
+        let b = wheel.schedule_with_handle(17, "B").unwrap();
+        let c = wheel.schedule_with_handle(19, "C").unwrap();
+        let d = wheel.schedule_with_handle(18, "D").unwrap();
+        wheel.tick(10);
+        // nothing is re-placed, so all four wait in the slot that came due
+        assert_eq!(wheel.count_by_level(), vec![0, 5]);
+        assert_eq!(wheel.next_expiry(), Some(1));
+        // unordered cancel still keeps the slot sorted, or the next timer could be skipped
+        assert_eq!(wheel.cancel(e), Some("E"));
+        assert_eq!(wheel.next_expiry(), Some(5));
+        assert_eq!(wheel.scheduled_at(a), Some(15));
+        wheel.check_invariants();
+
+        assert_eq!(wheel.lease_timer(b).as_deref(), Some(&"B"));
+        assert_eq!(wheel.extend_deadline(c, 3), Ok(c));
+        assert_eq!(wheel.shrink_deadline(a, 1).ok(), Some(a));
+        assert_eq!(wheel.count_by_level(), vec![2, 2]);
+        wheel.check_invariants();
+
+        assert_eq!(wheel.fire_all_within(7), vec!["A", "B"]);
+        assert_eq!(wheel.tick_until(d, 10).ok(), Some("D"));
+        assert_eq!(wheel.now(), 18);
+        assert_eq!(wheel.tick(4), vec!["C"]);
+    }
+
+    #[test]
+    fn test_min_delay_ticks() {
+        let mut wheel = WheelBuilder::new(2, 16, 10).with_min_delay_ticks(5).build();
+        for delay in 0..5 {
+            assert_eq!(wheel.schedule(delay, delay).unwrap(), (0, 5));
+        }
+        assert_eq!(wheel.schedule(7, 7).unwrap(), (0, 7));
+        assert_eq!(wheel.schedule_at_next_slot(8), (0, 5));
+
+        assert!(wheel.tick(4).is_empty());
+        assert_eq!(wheel.tick(1), vec![0, 1, 2, 3, 4, 8]);
+    }
+}
@@ -0,0 +1,237 @@
+use crate::{Entry, HierarchicalTimingWheel, Located, ScheduleError};
+
+/// Lazy removal iterator returned by `drain_filter`. Dropping it stops the removal, leaving
+/// matching timers it has not reached in the wheel.
+pub struct DrainFilter<'a, T, F> {
+    wheel: &'a mut HierarchicalTimingWheel<T>,
+    pred: F,
+    level: usize,
+    slot: usize,
+    index: usize,
+}
+
+impl<T, F: FnMut(&T) -> bool> Iterator for DrainFilter<'_, T, F> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        while let Some(ring) = self.wheel.rings.get_mut(self.level) {
+            let Some(slot) = ring.slots.get_mut(self.slot) else {
+                self.level += 1;
+                self.slot = 0;
+                continue;
+            };
+            while let Some(entry) = slot.get(self.index) {
+                if (self.pred)(&entry.timer) {
+                    let entry = slot.remove(self.index).expect("entry at index exists");
+                    return Some(self.wheel.release(entry));
+                }
+                self.index += 1;
+            }
+            self.slot += 1;
+            self.index = 0;
+        }
+        None
+    }
+}
+
+impl<T> HierarchicalTimingWheel<T> {
+    /// Removes and yields the pending timers matching `pred`, one at a time as the iterator is
+    /// advanced, in storage order rather than fire order.
+    pub fn drain_filter<F: FnMut(&T) -> bool>(&mut self, pred: F) -> DrainFilter<'_, T, F> {
+        DrainFilter {
+            wheel: self,
+            pred,
+            level: 0,
+            slot: 0,
+            index: 0,
+        }
+    }
+
+    /// Removes every pending timer whose tag is `target_id`, such as all timeouts of a closing
+    /// connection, and returns them in the order they would have fired.
+    pub fn fire_for_tag<F: Fn(&T) -> Option<u32>>(&mut self, target_id: u32, tag_fn: F) -> Vec<T> {
+        self.remove_matching(|timer| tag_fn(timer) == Some(target_id))
+            .into_iter()
+            .map(|(_, entry)| self.release(entry))
+            .collect()
+    }
+
+    /// Fires every pending timer due within the next `ticks_ahead` ticks, without advancing the
+    /// clock, and returns them in the order `tick` would have.
+    pub fn fire_all_within(&mut self, ticks_ahead: usize) -> Vec<T> {
+        self.remove_located(|located| located.due_in <= ticks_ahead)
+            .into_iter()
+            .map(|(_, entry)| self.fire_entry(entry))
+            .collect()
+    }
+
+    /// Removes the timers of the ring at `level` matching `pred`, leaving the other rings alone,
+    /// and returns them in the order they would have fired.
+    pub fn remove_where_in_level(
+        &mut self,
+        level: usize,
+        mut pred: impl FnMut(&T) -> bool,
+    ) -> Vec<T> {
+        let Some(ring) = self.rings.get_mut(level) else {
+            return Vec::new();
+        };
+        let slots = ring.slots.len();
+        let mut removed: Vec<Entry<T>> = Vec::new();
+        for offset in 1..=slots {
+            let slot = &mut ring.slots[(ring.cursor + offset) % slots];
+            let first = removed.len();
+            let mut index = 0;
+            while index < slot.len() {
+                if pred(&slot[index].timer) {
+                    removed.extend(slot.remove(index));
+                } else {
+                    index += 1;
+                }
+            }
+            removed[first..].sort_by_key(|entry| (entry.remaining, entry.id));
+        }
+        removed
+            .into_iter()
+            .map(|entry| self.release(entry))
+            .collect()
+    }
+
+    /// Re-places every pending timer matching `pred` as if it had just been scheduled with
+    /// `new_delay`, wherever it currently sits. Handles stay valid. Returns how many moved, or
+    /// `DelayTooLarge` without touching the wheel if `new_delay` does not fit.
+    pub fn reschedule_all_matching(
+        &mut self,
+        pred: impl Fn(&T) -> bool,
+        new_delay: usize,
+    ) -> Result<usize, ScheduleError> {
+        let delay = self.effective_delay(new_delay);
+        if !self.fits(delay) {
+            return Err(ScheduleError::DelayTooLarge);
+        }
+        let matching = self.remove_matching(pred);
+        let count = matching.len();
+        for (_, mut entry) in matching {
+            entry.requested = self.current_tick + delay.max(1) as u64;
+            self.place(delay, entry)
+                .ok()
+                .expect("delay was checked to fit");
+        }
+        Ok(count)
+    }
+
+    // Removes every pending timer matching `pred`, ordered by the number of ticks until it fires
+    // and then by scheduling order.
+    fn remove_matching(&mut self, pred: impl Fn(&T) -> bool) -> Vec<(usize, Entry<T>)> {
+        self.remove_located(|located| pred(&located.entry.timer))
+    }
+
+    fn remove_located(&mut self, pred: impl Fn(&Located<T>) -> bool) -> Vec<(usize, Entry<T>)> {
+        let mut matching: Vec<(usize, usize, usize, usize)> = self
+            .located()
+            .filter(|located| pred(located))
+            .map(|located| (located.level, located.slot, located.index, located.due_in))
+            .collect();
+        matching.sort_unstable_by(|a, b| b.cmp(a));
+
+        let mut removed: Vec<(usize, Entry<T>)> = matching
+            .into_iter()
+            .filter_map(|(level, slot, index, due_in)| {
+                let entry = self.rings[level].slots[slot].remove(index)?;
+                Some((due_in, entry))
+            })
+            .collect();
+        removed.sort_by_key(|(due_in, entry)| (*due_in, entry.id));
+        removed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TimerId;
+
+    #[test]
+    fn test_fire_for_tag() {
+        let mut wheel = HierarchicalTimingWheel::new(3, 16, 10);
+        wheel.schedule(150, (1, "idle")).unwrap();
+        wheel.schedule(5, (2, "read")).unwrap();
+        wheel.schedule(30, (1, "write")).unwrap();
+        wheel.schedule(5, (1, "read")).unwrap();
+
+        let fired = wheel.fire_for_tag(1, |(conn, _)| Some(*conn));
+        assert_eq!(fired, vec![(1, "read"), (1, "write"), (1, "idle")]);
+        assert!(wheel.fire_for_tag(1, |(conn, _)| Some(*conn)).is_empty());
+        assert_eq!(wheel.tick(200), vec![(2, "read")]);
+    }
+
+    #[test]
+    fn test_reschedule_all_matching() {
+        let mut wheel = HierarchicalTimingWheel::new(3, 16, 10);
+        wheel.schedule(3, ("tenant-x", 1)).unwrap();
+        wheel.schedule(40, ("tenant-x", 2)).unwrap();
+        wheel.schedule(400, ("tenant-x", 3)).unwrap();
+        wheel.schedule(50, ("tenant-y", 4)).unwrap();
+        wheel.tick(1);
+
+        let tenant_x = |(tenant, _): &(&str, u32)| *tenant == "tenant-x";
+        assert_eq!(wheel.reschedule_all_matching(tenant_x, 60).unwrap(), 3);
+        assert!(matches!(
+            wheel.reschedule_all_matching(tenant_x, 1000),
+            Err(ScheduleError::DelayTooLarge)
+        ));
+
+        assert_eq!(wheel.tick(49), vec![("tenant-y", 4)]);
+        assert_eq!(
+            wheel.tick(11),
+            vec![("tenant-x", 1), ("tenant-x", 2), ("tenant-x", 3)]
+        );
+        assert!(wheel.is_empty());
+    }
+
+    #[test]
+    fn test_remove_where_in_level() {
+        let mut wheel = HierarchicalTimingWheel::new(3, 16, 10);
+        for delay in [4, 8, 15, 31, 34, 36, 250] {
+            wheel.schedule(delay, delay).unwrap();
+        }
+
+        let removed = wheel.remove_where_in_level(1, |delay| delay % 2 == 0);
+        assert_eq!(removed, vec![34, 36]);
+        assert!(wheel.remove_where_in_level(3, |_| true).is_empty());
+        assert_eq!(wheel.count_by_level(), vec![2, 2, 1]);
+        assert_eq!(wheel.tick(250), vec![4, 8, 15, 31, 250]);
+    }
+
+    #[test]
+    fn test_fire_all_within() {
+        let mut wheel = HierarchicalTimingWheel::new(3, 16, 10);
+        for delay in [30, 2, 9, 12, 31, 150] {
+            wheel.schedule(delay, delay).unwrap();
+        }
+
+        assert_eq!(wheel.fire_all_within(30), vec![2, 9, 12, 30]);
+        assert_eq!(wheel.now(), 0);
+        assert!(wheel.fire_all_within(0).is_empty());
+        assert_eq!(wheel.tick(200), vec![31, 150]);
+    }
+
+    #[test]
+    fn test_drain_filter() {
+        let mut wheel = HierarchicalTimingWheel::new(3, 16, 10);
+        for delay in [4, 7, 15, 22, 60, 250] {
+            wheel.schedule(delay, delay).unwrap();
+        }
+        wheel.schedule_with_id(TimerId(1), 40, 40).unwrap();
+
+        let mut even = wheel.drain_filter(|delay| delay % 2 == 0);
+        assert_eq!(even.next(), Some(4));
+        assert_eq!(even.next(), Some(22));
+        assert_eq!(wheel.len(), 5);
+
+        let drained: Vec<_> = wheel.drain_filter(|&delay| delay >= 40).collect();
+        assert_eq!(drained, vec![40, 60, 250]);
+        assert_eq!(wheel.cancel_id(TimerId(1)), None);
+        wheel.check_invariants();
+        assert_eq!(wheel.tick(300), vec![7, 15]);
+    }
+}
@@ -0,0 +1,289 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::{HierarchicalTimingWheel, ScheduleError, TimerHandle};
+
+pub trait Clock {
+    fn now(&self) -> Instant;
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MonotonicClock;
+
+impl Clock for MonotonicClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// Manually advanced clock for deterministic tests. Clones share the same time.
+#[derive(Debug, Clone)]
+pub struct SimulatedClock {
+    now: Arc<Mutex<Instant>>,
+}
+
+impl SimulatedClock {
+    pub fn new() -> SimulatedClock {
+        Self {
+            now: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    pub fn advance(&self, by: Duration) {
+        *self.now.lock().unwrap() += by;
+    }
+}
+
+impl Default for SimulatedClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for SimulatedClock {
+    fn now(&self) -> Instant {
+        *self.now.lock().unwrap()
+    }
+}
+
+/// Drives a wheel from a clock, one tick per `resolution` of elapsed time.
+pub struct WallClockWheel<T, C = MonotonicClock> {
+    wheel: HierarchicalTimingWheel<T>,
+    clock: C,
+    resolution: Duration,
+    last_tick: Instant,
+    catch_up_budget: Option<u128>,
+}
+
+impl<T> WallClockWheel<T> {
+    pub fn new(wheel: HierarchicalTimingWheel<T>, resolution: Duration) -> WallClockWheel<T> {
+        Self::with_clock(wheel, resolution, MonotonicClock)
+    }
+}
+
+impl<T, C: Clock> WallClockWheel<T, C> {
+    pub fn with_clock(
+        wheel: HierarchicalTimingWheel<T>,
+        resolution: Duration,
+        clock: C,
+    ) -> WallClockWheel<T, C> {
+        assert!(!resolution.is_zero(), "resolution must be non-zero");
+        let last_tick = clock.now();
+        Self {
+            wheel,
+            clock,
+            resolution,
+            last_tick,
+            catch_up_budget: None,
+        }
+    }
+
+    pub fn wheel(&self) -> &HierarchicalTimingWheel<T> {
+        &self.wheel
+    }
+
+    pub fn wheel_mut(&mut self) -> &mut HierarchicalTimingWheel<T> {
+        &mut self.wheel
+    }
+
+    pub fn resolution(&self) -> Duration {
+        self.resolution
+    }
+
+    /// Limits each `poll_now` to advancing at most `ticks` ticks, so a wheel that fell far behind
+    /// catches up over several polls instead of firing everything overdue at once. `None`, the
+    /// default, advances all the way every time.
+    pub fn set_catch_up_budget(&mut self, ticks: Option<usize>) {
+        self.catch_up_budget = ticks.map(|ticks| ticks as u128);
+    }
+
+    /// Schedules `timer` for the first tick at or after `at`, placed like `schedule`, so a
+    /// deadline that lands in an upper ring may fire early. Deadlines that are not after the
+    /// wheel's current tick fire on the next one.
+    pub fn schedule_deadline(
+        &mut self,
+        at: Instant,
+        timer: T,
+    ) -> Result<(usize, usize), ScheduleError> {
+        let delay_ticks = self.ticks_until(at)?;
+        self.wheel.schedule(delay_ticks, timer)
+    }
+
+    /// Like `schedule_deadline`, but places `timer` exactly, so it does not fire early from an
+    /// upper ring, and returns a handle to cancel it with.
+    pub fn schedule_deadline_exact(
+        &mut self,
+        at: Instant,
+        timer: T,
+    ) -> Result<TimerHandle, ScheduleError> {
+        let delay_ticks = self.ticks_until(at)?;
+        let now = self.wheel.now();
+        self.wheel
+            .schedule_chained_with_handle(now, delay_ticks, timer)
+    }
+
+    fn ticks_until(&self, at: Instant) -> Result<usize, ScheduleError> {
+        let ahead = at.saturating_duration_since(self.last_tick).as_nanos();
+        let delay_ticks = ahead.div_ceil(self.resolution.as_nanos());
+        usize::try_from(delay_ticks).map_err(|_| ScheduleError::DelayTooLarge)
+    }
+
+    /// When the timer behind `handle` is expected to fire, assuming the wheel is polled on time,
+    /// or `None` if it is no longer pending.
+    pub fn estimate_fire_time(&self, handle: TimerHandle) -> Option<Instant> {
+        let due_in = self.wheel.scheduled_at(handle)? - self.wheel.now();
+        let ahead = self.resolution.checked_mul(u32::try_from(due_in).ok()?)?;
+        self.last_tick.checked_add(ahead)
+    }
+
+    /// Advances the wheel by every whole `resolution` elapsed since the last tick and returns
+    /// the timers that came due. The sub-resolution remainder carries over to the next poll, as
+    /// does any time beyond the catch-up budget.
+    pub fn poll_now(&mut self) -> Vec<T> {
+        let elapsed = self.clock.now().saturating_duration_since(self.last_tick);
+        let mut ticks = elapsed.as_nanos() / self.resolution.as_nanos();
+        if let Some(budget) = self.catch_up_budget {
+            ticks = ticks.min(budget);
+        }
+        self.last_tick += Duration::from_nanos((ticks * self.resolution.as_nanos()) as u64);
+        self.wheel.tick(ticks as usize)
+    }
+}
+
+/// How many ticks the first of two wheels that should advance in lockstep is ahead of the
+/// second; negative when it is behind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockSkew {
+    pub ticks_ahead: i64,
+}
+
+pub fn detect_skew<T, C1: Clock, C2: Clock>(
+    wheel1: &WallClockWheel<T, C1>,
+    wheel2: &WallClockWheel<T, C2>,
+) -> ClockSkew {
+    let (now1, now2) = (wheel1.wheel.now(), wheel2.wheel.now());
+    ClockSkew {
+        ticks_ahead: now1 as i64 - now2 as i64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hierarchical;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    #[test]
+    fn test_poll_keeps_sub_resolution_remainder() {
+        let clock = SimulatedClock::new();
+        let mut wheel = WallClockWheel::with_clock(hierarchical(2, 16, 10), 10 * MS, clock.clone());
+        wheel.schedule_deadline(clock.now() + 35 * MS, "A").unwrap();
+
+        clock.advance(25 * MS);
+        assert!(wheel.poll_now().is_empty());
+        assert_eq!(wheel.wheel().now(), 2);
+        assert!(wheel.poll_now().is_empty());
+        assert_eq!(wheel.wheel().now(), 2);
+
+        clock.advance(15 * MS);
+        assert_eq!(wheel.poll_now(), vec!["A"]);
+        assert_eq!(wheel.wheel().now(), 4);
+    }
+
+    #[test]
+    fn test_long_gap_fires_everything_in_between() {
+        let clock = SimulatedClock::new();
+        let mut wheel = WallClockWheel::with_clock(hierarchical(3, 16, 10), MS, clock.clone());
+        let start = clock.now();
+        wheel.schedule_deadline(start, 0).unwrap();
+        for ms in [1, 90, 450, 999] {
+            wheel.schedule_deadline(start + ms * MS, ms).unwrap();
+        }
+
+        clock.advance(1000 * MS);
+        assert_eq!(wheel.poll_now(), vec![0, 1, 90, 450, 999]);
+        assert!(wheel.poll_now().is_empty());
+    }
+
+    #[test]
+    fn test_catch_up_budget() {
+        let clock = SimulatedClock::new();
+        let mut wheel = WallClockWheel::with_clock(hierarchical(2, 16, 10), 10 * MS, clock.clone());
+        for n in 1..=30 {
+            wheel
+                .schedule_deadline(clock.now() + n * 10 * MS, n)
+                .unwrap();
+        }
+        wheel.set_catch_up_budget(Some(10));
+
+        // suspended for 50 ticks
+        clock.advance(505 * MS);
+        assert_eq!(wheel.poll_now(), (1..=10).collect::<Vec<_>>());
+        assert_eq!(wheel.poll_now(), (11..=20).collect::<Vec<_>>());
+        assert_eq!(wheel.wheel().now(), 20);
+
+        wheel.set_catch_up_budget(None);
+        assert_eq!(wheel.poll_now(), (21..=30).collect::<Vec<_>>());
+        assert_eq!(wheel.wheel().now(), 50);
+        clock.advance(5 * MS);
+        assert_eq!(wheel.poll_now(), vec![]);
+        assert_eq!(wheel.wheel().now(), 51);
+    }
+
+    #[test]
+    fn test_detect_skew() {
+        let (clock1, clock2) = (SimulatedClock::new(), SimulatedClock::new());
+        let mut wheel1 =
+            WallClockWheel::with_clock(hierarchical::<()>(2, 16, 10), MS, clock1.clone());
+        let mut wheel2 =
+            WallClockWheel::with_clock(hierarchical::<()>(2, 16, 10), MS, clock2.clone());
+
+        clock1.advance(12 * MS);
+        clock2.advance(9 * MS);
+        wheel1.poll_now();
+        wheel2.poll_now();
+        assert_eq!(detect_skew(&wheel1, &wheel2), ClockSkew { ticks_ahead: 3 });
+        assert_eq!(detect_skew(&wheel2, &wheel1), ClockSkew { ticks_ahead: -3 });
+    }
+
+    #[test]
+    fn test_estimate_fire_time() {
+        let clock = SimulatedClock::new();
+        let start = clock.now();
+        let mut wheel = WallClockWheel::with_clock(hierarchical(2, 16, 10), 10 * MS, clock.clone());
+        clock.advance(25 * MS);
+        wheel.poll_now();
+
+        let handle = wheel.wheel_mut().schedule_with_handle(5, "A").unwrap();
+        assert_eq!(wheel.estimate_fire_time(handle), Some(start + 70 * MS));
+        clock.advance(50 * MS);
+        assert_eq!(wheel.poll_now(), vec!["A"]);
+        assert_eq!(wheel.estimate_fire_time(handle), None);
+    }
+
+    #[test]
+    fn test_schedule_deadline_exact() {
+        let clock = SimulatedClock::new();
+        let start = clock.now();
+        let mut wheel = WallClockWheel::with_clock(hierarchical(2, 16, 10), MS, clock.clone());
+        clock.advance(5 * MS);
+        wheel.poll_now();
+
+        // 25 ticks from tick 5 sit in ring 1, which `schedule_deadline` aligns to fire at tick 25
+        wheel.schedule_deadline(start + 30 * MS, "aligned").unwrap();
+        let handle = wheel
+            .schedule_deadline_exact(start + 30 * MS, "exact")
+            .unwrap();
+        let cancelled = wheel
+            .schedule_deadline_exact(start + 30 * MS, "cancelled")
+            .unwrap();
+        assert_eq!(wheel.estimate_fire_time(handle), Some(start + 30 * MS));
+        assert_eq!(wheel.wheel_mut().cancel(cancelled), Some("cancelled"));
+
+        clock.advance(20 * MS);
+        assert_eq!(wheel.poll_now(), vec!["aligned"]);
+        clock.advance(5 * MS);
+        assert_eq!(wheel.poll_now(), vec!["exact"]);
+    }
+}
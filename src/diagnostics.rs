@@ -0,0 +1,252 @@
+use std::time::Instant;
+
+use crate::HierarchicalTimingWheel;
+
+/// How many of the most recent ticks `fire_count_since` remembers.
+pub const FIRE_HISTORY_TICKS: u64 = 1024;
+
+/// What a single `tick_with_diagnostics` call did internally.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TickDiagnostics {
+    pub steps_taken: usize,
+    /// Ring advances, counting level 0 once per step and upper rings on each carry.
+    pub rings_ticked: usize,
+    /// Timers taken out of an upper ring because their slot came due.
+    pub graduations: usize,
+    /// Graduated timers placed into a lower ring rather than fired.
+    pub reschedules: usize,
+    pub wall_time_ns: u64,
+}
+
+/// Timers that fired before the tick they were scheduled for, because a timer placed in an upper
+/// ring is aligned to that ring's rotation rather than to the tick it was scheduled at.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EarlyFirings {
+    pub timers: u64,
+    /// Sum over those timers of how many ticks early each one fired.
+    pub ticks: u64,
+}
+
+/// Timers that moved from one ring to another at one tick, as returned by
+/// `tick_collecting_cascades`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CascadeRecord {
+    pub tick: u64,
+    pub from_level: usize,
+    pub to_level: usize,
+    pub count: usize,
+}
+
+impl<T> HierarchicalTimingWheel<T> {
+    pub fn early_firings(&self) -> EarlyFirings {
+        self.early_firings
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.early_firings = EarlyFirings::default();
+        self.fire_history.clear();
+    }
+
+    /// Number of timers fired at or after the absolute tick `tick`, at a resolution of one tick.
+    /// Only the last `FIRE_HISTORY_TICKS` ticks are remembered, so older ticks are not counted.
+    pub fn fire_count_since(&self, tick: u64) -> usize {
+        let horizon = self.current_tick.saturating_sub(FIRE_HISTORY_TICKS - 1);
+        self.fire_history
+            .iter()
+            .filter(|(fired_at, _)| *fired_at >= tick.max(horizon))
+            .map(|(_, count)| count)
+            .sum()
+    }
+
+    // Counts a timer fired at the current tick, forgetting ticks past the history's horizon.
+    pub(crate) fn record_fire(&mut self) {
+        let now = self.current_tick;
+        match self.fire_history.back_mut() {
+            Some((tick, count)) if *tick == now => *count += 1,
+            _ => {
+                while self
+                    .fire_history
+                    .front()
+                    .is_some_and(|(tick, _)| tick + FIRE_HISTORY_TICKS <= now)
+                {
+                    self.fire_history.pop_front();
+                }
+                self.fire_history.push_back((now, 1));
+            }
+        }
+    }
+
+    /// Calls `f(level, count)` whenever `count` timers graduate out of the ring at `level`. `f`
+    /// must be `Send + Sync` so the wheel stays both.
+    pub fn on_cascade(&mut self, f: impl FnMut(usize, usize) + Send + Sync + 'static) {
+        self.on_cascade = Some(Box::new(f));
+    }
+
+    /// Like `tick`, but also returns every move of graduating timers to a lower ring, one record
+    /// per tick and pair of rings. Timers that fire straight from an upper ring are not moves.
+    pub fn tick_collecting_cascades(&mut self, steps: usize) -> (Vec<T>, Vec<CascadeRecord>) {
+        self.cascade_log = Some(Vec::new());
+        let due = self.tick(steps);
+        (due, self.cascade_log.take().unwrap_or_default())
+    }
+
+    // Counts one timer moving between rings in the log `tick_collecting_cascades` keeps.
+    pub(crate) fn log_cascade(&mut self, from_level: usize, to_level: usize) {
+        let Some(log) = self.cascade_log.as_mut() else {
+            return;
+        };
+        let tick = self.current_tick;
+        let existing = log
+            .iter_mut()
+            .rev()
+            .take_while(|record| record.tick == tick)
+            .find(|record| record.from_level == from_level && record.to_level == to_level);
+        match existing {
+            Some(record) => record.count += 1,
+            None => log.push(CascadeRecord {
+                tick,
+                from_level,
+                to_level,
+                count: 1,
+            }),
+        }
+    }
+
+    pub fn tick_with_diagnostics(&mut self, steps: usize) -> (Vec<T>, TickDiagnostics) {
+        let started = Instant::now();
+        let mut due: Vec<T> = self.undelivered.drain(..).collect();
+        let mut diagnostics = TickDiagnostics::default();
+        for _ in 0..steps {
+            self.step(&mut diagnostics, |timer| due.push(timer));
+        }
+        diagnostics.wall_time_ns = started.elapsed().as_nanos() as u64;
+        (due, diagnostics)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    #[test]
+    fn test_tick_with_diagnostics() {
+        let mut wheel = HierarchicalTimingWheel::new(3, 16, 10);
+        wheel.schedule(5, "A").unwrap();
+        wheel.schedule(20, "B").unwrap();
+        wheel.schedule(23, "C").unwrap();
+        wheel.schedule(100, "D").unwrap();
+
+        let (due, diagnostics) = wheel.tick_with_diagnostics(25);
+        assert_eq!(due, vec!["A", "B", "C"]);
+        assert_eq!(diagnostics.steps_taken, 25);
+        // 25 level 0 ticks and 2 level 1 ticks
+        assert_eq!(diagnostics.rings_ticked, 27);
+        // B and C graduate at tick 20, only C needs another placement
+        assert_eq!(diagnostics.graduations, 2);
+        assert_eq!(diagnostics.reschedules, 1);
+
+        let (due, diagnostics) = wheel.tick_with_diagnostics(75);
+        assert_eq!(due, vec!["D"]);
+        assert_eq!(diagnostics.rings_ticked, 75 + 8 + 1);
+        assert_eq!(diagnostics.graduations, 1);
+        assert_eq!(diagnostics.reschedules, 0);
+    }
+
+    #[test]
+    fn test_early_firings() {
+        let mut wheel = HierarchicalTimingWheel::new(3, 16, 10);
+        wheel.schedule(25, "exact").unwrap();
+        wheel.tick(7);
+        wheel.schedule(2, "exact").unwrap();
+        // both land at level 1 while level 0 is 7 ticks into its rotation, so both fire 7 early
+        wheel.schedule(17, "early").unwrap();
+        wheel.schedule(22, "early").unwrap();
+
+        wheel.tick(100);
+        assert_eq!(
+            wheel.early_firings(),
+            EarlyFirings {
+                timers: 2,
+                ticks: 14
+            }
+        );
+        wheel.reset_stats();
+        assert_eq!(wheel.early_firings(), EarlyFirings::default());
+    }
+
+    #[test]
+    fn test_on_cascade() {
+        let mut wheel = HierarchicalTimingWheel::new(3, 16, 10);
+        for delay in [20, 25, 23, 100, 150] {
+            wheel.schedule(delay, delay).unwrap();
+        }
+        let cascades = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&cascades);
+        wheel.on_cascade(move |level, count| log.lock().unwrap().push((level, count)));
+        fn is_sync<T: Sync>(_: &T) {}
+        is_sync(&wheel);
+
+        wheel.tick(19);
+        assert!(cascades.lock().unwrap().is_empty());
+        wheel.tick(1);
+        assert_eq!(*cascades.lock().unwrap(), [(1, 3)]);
+        wheel.tick(79);
+        assert_eq!(cascades.lock().unwrap().len(), 1);
+        wheel.tick(1);
+        assert_eq!(*cascades.lock().unwrap(), [(1, 3), (2, 2)]);
+        wheel.tick(50);
+        assert_eq!(*cascades.lock().unwrap(), [(1, 3), (2, 2), (1, 1)]);
+    }
+
+    #[test]
+    fn test_fire_count_since() {
+        let mut wheel = HierarchicalTimingWheel::new(3, 16, 10);
+        for delay in [2, 2, 5, 9, 9, 9] {
+            wheel.schedule(delay, delay).unwrap();
+        }
+        wheel.tick(10);
+        assert_eq!(wheel.fire_count_since(0), 6);
+        assert_eq!(wheel.fire_count_since(5), 4);
+        assert_eq!(wheel.fire_count_since(6), 3);
+        assert_eq!(wheel.fire_count_since(10), 0);
+
+        wheel.schedule(1, 1).unwrap();
+        wheel.tick(FIRE_HISTORY_TICKS as usize);
+        // only the last timer is still within the history
+        assert_eq!(wheel.fire_count_since(0), 1);
+        wheel.reset_stats();
+        assert_eq!(wheel.fire_count_since(0), 0);
+    }
+
+    #[test]
+    fn test_tick_collecting_cascades() {
+        let mut wheel = HierarchicalTimingWheel::new(3, 16, 10);
+        for delay in [5, 20, 23, 25, 100, 137, 205] {
+            wheel.schedule(delay, delay).unwrap();
+        }
+
+        let record = |tick, from_level, to_level, count| CascadeRecord {
+            tick,
+            from_level,
+            to_level,
+            count,
+        };
+        let (due, cascades) = wheel.tick_collecting_cascades(150);
+        assert_eq!(due, vec![5, 20, 23, 25, 100, 137]);
+        // 20 and 100 fire straight from their ring, 137 moves down twice
+        assert_eq!(
+            cascades,
+            [
+                record(20, 1, 0, 2),
+                record(100, 2, 1, 1),
+                record(130, 1, 0, 1)
+            ]
+        );
+        assert_eq!(wheel.count_by_level(), vec![0, 0, 1]);
+        let (_, cascades) = wheel.tick_collecting_cascades(60);
+        assert_eq!(cascades, [record(200, 2, 0, 1)]);
+        assert!(wheel.tick_collecting_cascades(0).1.is_empty());
+    }
+}
@@ -0,0 +1,119 @@
+use std::fmt;
+
+use crate::HierarchicalTimingWheel;
+
+/// Differences between two wheels, as reported by `diff`. Timers are identified by the tick
+/// they fire at together with their payload.
+#[derive(Debug, PartialEq, Eq)]
+pub struct WheelDiff<'a, T> {
+    /// `(self, other)` ticks, when they differ.
+    pub now: Option<(u64, u64)>,
+    /// `(level, self, other)` for every ring whose cursor differs.
+    pub cursors: Vec<(usize, usize, usize)>,
+    /// `(fire tick, timer)` pending only in `self`.
+    pub only_in_self: Vec<(u64, &'a T)>,
+    /// `(fire tick, timer)` pending only in `other`.
+    pub only_in_other: Vec<(u64, &'a T)>,
+}
+
+impl<T> WheelDiff<'_, T> {
+    pub fn is_empty(&self) -> bool {
+        self.now.is_none()
+            && self.cursors.is_empty()
+            && self.only_in_self.is_empty()
+            && self.only_in_other.is_empty()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Display for WheelDiff<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some((left, right)) = self.now {
+            writeln!(f, "now: {left} != {right}")?;
+        }
+        for (level, left, right) in &self.cursors {
+            writeln!(f, "level {level} cursor: {left} != {right}")?;
+        }
+        for (tick, timer) in &self.only_in_self {
+            writeln!(f, "- {timer:?} at tick {tick}")?;
+        }
+        for (tick, timer) in &self.only_in_other {
+            writeln!(f, "+ {timer:?} at tick {tick}")?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: PartialEq> HierarchicalTimingWheel<T> {
+    pub fn diff<'a>(&'a self, other: &'a Self) -> WheelDiff<'a, T> {
+        let now = (self.current_tick != other.current_tick)
+            .then_some((self.current_tick, other.current_tick));
+        let cursors = self
+            .rings
+            .iter()
+            .zip(&other.rings)
+            .enumerate()
+            .filter(|(_, (left, right))| left.cursor != right.cursor)
+            .map(|(level, (left, right))| (level, left.cursor, right.cursor))
+            .collect();
+
+        let mut only_in_self = self.pending();
+        let mut only_in_other = Vec::new();
+        for pending in other.pending() {
+            match only_in_self.iter().position(|mine| *mine == pending) {
+                Some(index) => {
+                    only_in_self.remove(index);
+                }
+                None => only_in_other.push(pending),
+            }
+        }
+        WheelDiff {
+            now,
+            cursors,
+            only_in_self,
+            only_in_other,
+        }
+    }
+
+    // `(fire tick, timer)` of every pending timer, soonest first.
+    fn pending(&self) -> Vec<(u64, &T)> {
+        let mut pending: Vec<(u64, u64, &T)> = self
+            .located()
+            .map(|located| {
+                let tick = self.current_tick + located.due_in as u64;
+                (tick, located.entry.id, &located.entry.timer)
+            })
+            .collect();
+        pending.sort_by_key(|(tick, id, _)| (*tick, *id));
+        pending
+            .into_iter()
+            .map(|(tick, _, timer)| (tick, timer))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        let mut expected = HierarchicalTimingWheel::new(2, 16, 10);
+        let mut actual = HierarchicalTimingWheel::new(2, 16, 10);
+        for wheel in [&mut expected, &mut actual] {
+            wheel.schedule(5, "A").unwrap();
+            wheel.schedule(40, "B").unwrap();
+        }
+        assert!(expected.diff(&actual).is_empty());
+
+        actual.schedule(12, "C").unwrap();
+        let diff = expected.diff(&actual);
+        assert_eq!(diff.only_in_other, vec![(12, &"C")]);
+        assert!(diff.only_in_self.is_empty());
+        assert_eq!(diff.to_string(), "+ \"C\" at tick 12\n");
+
+        expected.tick(1);
+        let diff = expected.diff(&actual);
+        assert_eq!(diff.now, Some((1, 0)));
+        assert_eq!(diff.cursors, vec![(0, 1, 0)]);
+    }
+}
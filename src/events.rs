@@ -0,0 +1,173 @@
+use std::sync::Arc;
+
+use crate::{HierarchicalTimingWheel, ScheduleError, TimerHandle};
+
+/// A state change reported to the callback of an `EventedWheel`.
+#[derive(Debug)]
+pub enum WheelEvent<'a, T> {
+    Scheduled {
+        delay: usize,
+        timer: &'a T,
+    },
+    Fired {
+        timer: &'a T,
+    },
+    /// An upper ring's slot came due and the timer moved to a lower ring.
+    Graduated {
+        timer: &'a T,
+        from_level: usize,
+        to_level: usize,
+    },
+    Cancelled {
+        timer: &'a T,
+    },
+}
+
+pub(crate) type Observer<T> = Arc<dyn Fn(WheelEvent<'_, T>) + Send + Sync>;
+
+/// Wheel that reports every schedule, firing, graduation and cancellation to a callback, as
+/// returned by `on_event`.
+pub struct EventedWheel<T> {
+    wheel: HierarchicalTimingWheel<T>,
+    observer: Observer<T>,
+}
+
+impl<T> HierarchicalTimingWheel<T> {
+    pub fn on_event<F>(mut self, f: F) -> EventedWheel<T>
+    where
+        F: Fn(WheelEvent<'_, T>) + Send + Sync + 'static,
+    {
+        let observer: Observer<T> = Arc::new(f);
+        self.observer = Some(Arc::clone(&observer));
+        EventedWheel {
+            wheel: self,
+            observer,
+        }
+    }
+
+    // Reports a graduated timer that was just placed at `(to_level, slot)`.
+    pub(crate) fn report_graduation(
+        &mut self,
+        from_level: usize,
+        (to_level, slot): (usize, usize),
+    ) {
+        self.log_cascade(from_level, to_level);
+        if let Some(observer) = &self.observer
+            && let Some(entry) = self.rings[to_level].slots[slot].back()
+        {
+            observer(WheelEvent::Graduated {
+                timer: &entry.timer,
+                from_level,
+                to_level,
+            });
+        }
+    }
+}
+
+impl<T> EventedWheel<T> {
+    pub fn schedule(
+        &mut self,
+        delay_ticks: usize,
+        timer: T,
+    ) -> Result<(usize, usize), ScheduleError> {
+        let (level, slot) = self.wheel.schedule(delay_ticks, timer)?;
+        self.report_scheduled(delay_ticks, level, slot);
+        Ok((level, slot))
+    }
+
+    pub fn schedule_with_handle(
+        &mut self,
+        delay_ticks: usize,
+        timer: T,
+    ) -> Result<TimerHandle, ScheduleError> {
+        let (handle, placement) = self.wheel.schedule_full(delay_ticks, timer)?;
+        self.report_scheduled(delay_ticks, placement.level, placement.slot);
+        Ok(handle)
+    }
+
+    pub fn cancel(&mut self, handle: TimerHandle) -> Option<T> {
+        let timer = self.wheel.cancel(handle)?;
+        (self.observer)(WheelEvent::Cancelled { timer: &timer });
+        Some(timer)
+    }
+
+    pub fn tick(&mut self, steps: usize) -> Vec<T> {
+        let mut due = Vec::new();
+        let observer = &self.observer;
+        self.wheel.tick_fn(steps, |timer| {
+            observer(WheelEvent::Fired { timer: &timer });
+            due.push(timer);
+        });
+        due
+    }
+
+    pub fn wheel(&self) -> &HierarchicalTimingWheel<T> {
+        &self.wheel
+    }
+
+    /// Stops reporting events and returns the wheel.
+    pub fn into_inner(mut self) -> HierarchicalTimingWheel<T> {
+        self.wheel.observer = None;
+        self.wheel
+    }
+
+    // a newly scheduled timer is always the last one of its slot
+    fn report_scheduled(&self, delay: usize, level: usize, slot: usize) {
+        if let Some(entry) = self.wheel.rings[level].slots[slot].back() {
+            (self.observer)(WheelEvent::Scheduled {
+                delay,
+                timer: &entry.timer,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    #[test]
+    fn test_on_event() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&log);
+        let mut wheel = HierarchicalTimingWheel::new(3, 16, 10).on_event(move |event| {
+            let line = match event {
+                WheelEvent::Scheduled { delay, timer } => format!("scheduled {timer} in {delay}"),
+                WheelEvent::Fired { timer } => format!("fired {timer}"),
+                WheelEvent::Graduated {
+                    timer,
+                    from_level,
+                    to_level,
+                } => format!("graduated {timer} from {from_level} to {to_level}"),
+                WheelEvent::Cancelled { timer } => format!("cancelled {timer}"),
+            };
+            sink.lock().unwrap().push(line);
+        });
+
+        wheel.schedule(3, "A").unwrap();
+        wheel.schedule(125, "B").unwrap();
+        let c = wheel.schedule_with_handle(40, "C").unwrap();
+        assert_eq!(wheel.cancel(c), Some("C"));
+        assert_eq!(wheel.tick(125), vec!["A", "B"]);
+        assert_eq!(
+            *log.lock().unwrap(),
+            [
+                "scheduled A in 3",
+                "scheduled B in 125",
+                "scheduled C in 40",
+                "cancelled C",
+                "fired A",
+                "graduated B from 2 to 1",
+                "graduated B from 1 to 0",
+                "fired B",
+            ]
+        );
+
+        let mut wheel = wheel.into_inner();
+        wheel.schedule(1, "D").unwrap();
+        wheel.tick(1);
+        assert_eq!(log.lock().unwrap().len(), 8);
+    }
+}
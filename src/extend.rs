@@ -0,0 +1,70 @@
+use crate::{HierarchicalTimingWheel, ScheduleError};
+
+/// Fallible counterpart of `Extend` that stops at the first item that cannot be inserted.
+pub trait TryExtend<A> {
+    type Error;
+
+    fn try_extend<I: IntoIterator<Item = A>>(&mut self, iter: I) -> Result<(), Self::Error>;
+}
+
+/// Returned by `try_extend`: why insertion stopped, and every item that was not inserted,
+/// starting with the one that failed.
+#[derive(Debug)]
+pub struct ExtendError<T> {
+    pub cause: ScheduleError,
+    pub remaining: Vec<(usize, T)>,
+}
+
+/// Schedules every `(delay_ticks, timer)` pair, silently dropping timers that do not fit.
+/// Use `try_extend` to find out which ones were rejected.
+impl<T> Extend<(usize, T)> for HierarchicalTimingWheel<T> {
+    fn extend<I: IntoIterator<Item = (usize, T)>>(&mut self, iter: I) {
+        for (delay_ticks, timer) in iter {
+            let _ = self.schedule(delay_ticks, timer);
+        }
+    }
+}
+
+impl<T> TryExtend<(usize, T)> for HierarchicalTimingWheel<T> {
+    type Error = ExtendError<T>;
+
+    fn try_extend<I: IntoIterator<Item = (usize, T)>>(
+        &mut self,
+        iter: I,
+    ) -> Result<(), ExtendError<T>> {
+        let mut iter = iter.into_iter();
+        while let Some((delay_ticks, timer)) = iter.next() {
+            if let Err((cause, timer)) = self.try_schedule(delay_ticks, timer) {
+                let mut remaining = vec![(delay_ticks, timer)];
+                remaining.extend(iter);
+                return Err(ExtendError { cause, remaining });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extend_ignores_errors() {
+        let mut wheel = HierarchicalTimingWheel::new(1, 16, 10);
+        wheel.extend([(1, "A"), (10, "B"), (2, "C")]);
+        assert_eq!(wheel.tick(2), vec!["A", "C"]);
+    }
+
+    #[test]
+    fn test_try_extend_returns_remaining() {
+        let mut wheel = HierarchicalTimingWheel::new(1, 16, 10);
+        let err = wheel
+            .try_extend([(1, "A"), (10, "B"), (2, "C")])
+            .unwrap_err();
+
+        assert!(matches!(err.cause, ScheduleError::DelayTooLarge));
+        assert_eq!(err.remaining, vec![(10, "B"), (2, "C")]);
+        assert_eq!(wheel.tick(2), vec!["A"]);
+        assert!(wheel.try_extend([(3, "D")]).is_ok());
+    }
+}
@@ -0,0 +1,98 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use crate::{HierarchicalTimingWheel, ScheduleError};
+
+/// Wheel of futures that start running once their delay has elapsed. Fired futures that are
+/// not complete after one poll are kept and polled again by every later `poll_fired`.
+pub struct FutureWheel<T: Future> {
+    wheel: HierarchicalTimingWheel<Pin<Box<T>>>,
+    running: Vec<Pin<Box<T>>>,
+}
+
+impl<T: Future> FutureWheel<T> {
+    pub fn new(levels: u32, slot_capacity: usize, slots_per_level: usize) -> FutureWheel<T> {
+        Self {
+            wheel: HierarchicalTimingWheel::new(levels, slot_capacity, slots_per_level),
+            running: Vec::new(),
+        }
+    }
+
+    pub fn schedule(
+        &mut self,
+        delay_ticks: usize,
+        future: T,
+    ) -> Result<(usize, usize), ScheduleError> {
+        self.wheel.schedule(delay_ticks, Box::pin(future))
+    }
+
+    /// Futures that fired but have not completed yet.
+    pub fn running(&self) -> usize {
+        self.running.len()
+    }
+
+    /// Ticks `steps` times, then polls the still running futures followed by the newly fired
+    /// ones, and returns the outputs of those that completed.
+    pub fn poll_fired(&mut self, cx: &mut Context<'_>, steps: usize) -> Vec<T::Output> {
+        let fired = self.wheel.tick(steps);
+        self.running.extend(fired);
+        let mut outputs = Vec::new();
+        self.running
+            .retain_mut(|future| match future.as_mut().poll(cx) {
+                Poll::Ready(output) => {
+                    outputs.push(output);
+                    false
+                }
+                Poll::Pending => true,
+            });
+        outputs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::{self, Ready};
+    use std::task::Waker;
+
+    use super::*;
+
+    // Pending on its first poll, ready with its value on the second.
+    struct YieldOnce(Option<u32>, bool);
+
+    impl Future for YieldOnce {
+        type Output = u32;
+
+        fn poll(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<u32> {
+            if std::mem::replace(&mut self.1, true) {
+                Poll::Ready(self.0.take().unwrap())
+            } else {
+                Poll::Pending
+            }
+        }
+    }
+
+    #[test]
+    fn test_poll_fired() {
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut wheel: FutureWheel<Ready<u32>> = FutureWheel::new(2, 16, 10);
+        wheel.schedule(3, future::ready(1)).unwrap();
+        wheel.schedule(25, future::ready(2)).unwrap();
+
+        assert!(wheel.poll_fired(&mut cx, 2).is_empty());
+        assert_eq!(wheel.poll_fired(&mut cx, 1), vec![1]);
+        assert_eq!(wheel.poll_fired(&mut cx, 22), vec![2]);
+    }
+
+    #[test]
+    fn test_poll_fired_keeps_pending_futures() {
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut wheel = FutureWheel::new(2, 16, 10);
+        wheel.schedule(1, YieldOnce(Some(7), false)).unwrap();
+
+        assert!(wheel.poll_fired(&mut cx, 1).is_empty());
+        assert_eq!(wheel.running(), 1);
+        assert_eq!(wheel.poll_fired(&mut cx, 0), vec![7]);
+        assert_eq!(wheel.running(), 0);
+    }
+}
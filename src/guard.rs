@@ -0,0 +1,70 @@
+use crate::{HierarchicalTimingWheel, ScheduleError, TimerHandle};
+
+/// Cancels its timer when dropped, unless `defuse` is called first. The guard borrows the
+/// wheel mutably, so the wheel is only reachable through `wheel` while the guard lives.
+pub struct WheelGuard<'a, T> {
+    wheel: &'a mut HierarchicalTimingWheel<T>,
+    handle: TimerHandle,
+    armed: bool,
+}
+
+impl<'a, T> WheelGuard<'a, T> {
+    pub fn handle(&self) -> TimerHandle {
+        self.handle
+    }
+
+    pub fn wheel(&mut self) -> &mut HierarchicalTimingWheel<T> {
+        self.wheel
+    }
+
+    /// Keeps the timer scheduled and returns its handle.
+    pub fn defuse(mut self) -> TimerHandle {
+        self.armed = false;
+        self.handle
+    }
+}
+
+impl<T> Drop for WheelGuard<'_, T> {
+    fn drop(&mut self) {
+        if self.armed {
+            self.wheel.cancel(self.handle);
+        }
+    }
+}
+
+impl<T> HierarchicalTimingWheel<T> {
+    pub fn schedule_guarded(
+        &mut self,
+        delay_ticks: usize,
+        timer: T,
+    ) -> Result<WheelGuard<'_, T>, ScheduleError> {
+        let handle = self.schedule_with_handle(delay_ticks, timer)?;
+        Ok(WheelGuard {
+            wheel: self,
+            handle,
+            armed: true,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_guard_cancels_on_drop() {
+        let mut wheel = HierarchicalTimingWheel::new(2, 16, 10);
+        let mut guard = wheel.schedule_guarded(5, "A").unwrap();
+        guard.wheel().schedule(5, "B").unwrap();
+        drop(guard);
+        assert_eq!(wheel.tick(5), vec!["B"]);
+    }
+
+    #[test]
+    fn test_defuse() {
+        let mut wheel = HierarchicalTimingWheel::new(2, 16, 10);
+        let handle = wheel.schedule_guarded(5, "A").unwrap().defuse();
+        assert_eq!(wheel.tick(5), vec!["A"]);
+        assert_eq!(wheel.cancel(handle), None);
+    }
+}
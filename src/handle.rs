@@ -0,0 +1,477 @@
+use std::collections::VecDeque;
+
+#[cfg(not(feature = "fast-cancel"))]
+use crate::filter_bits;
+use crate::{Entry, HierarchicalTimingWheel, OrderingMode, ScheduleError, TickDiagnostics};
+
+/// Identifies a timer scheduled through `schedule_with_handle`. A handle is only meaningful to
+/// the wheel that issued it; passing it to any other wheel panics. `with_brand` issues
+/// `BrandedHandle`s, for which that mistake is a compile error instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimerHandle {
+    pub(crate) wheel: u64,
+    pub(crate) id: u64,
+}
+
+/// Caller-chosen identifier for a timer scheduled through `schedule_with_id`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimerId(pub u64);
+
+/// Where `schedule_full` put a timer, and the delay it was placed with once the overflow policy
+/// and delay floor were applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Placement {
+    pub level: usize,
+    pub slot: usize,
+    pub effective_delay: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TickUntilError {
+    /// The handle's timer is not pending, because it already fired or was cancelled.
+    NotFound,
+    /// `max_steps` ran out `remaining_delay` ticks before the timer was due.
+    StepsExhausted { remaining_delay: usize },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExtendDeadlineError {
+    /// The handle's timer is not pending, because it already fired or was cancelled.
+    NotFound,
+    /// The extended deadline is further out than the wheel reaches.
+    NewDelayTooLarge,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ShrinkError<T> {
+    /// The handle's timer is not pending, because it already fired or was cancelled.
+    NotFound,
+    /// The shortened delay reached the current tick; the timer was taken out and is handed back
+    /// here instead of waiting for the next tick.
+    AlreadyDue(T),
+}
+
+impl<T> HierarchicalTimingWheel<T> {
+    pub fn schedule_with_handle(
+        &mut self,
+        delay_ticks: usize,
+        timer: T,
+    ) -> Result<TimerHandle, ScheduleError> {
+        let id = self.next_id;
+        self.schedule(delay_ticks, timer)?;
+        Ok(TimerHandle { wheel: self.id, id })
+    }
+
+    /// `schedule_chained` that returns a handle to the timer.
+    pub fn schedule_chained_with_handle(
+        &mut self,
+        prev_fire_tick: u64,
+        delay_ticks: usize,
+        timer: T,
+    ) -> Result<TimerHandle, ScheduleError> {
+        let id = self.next_id;
+        self.schedule_chained(prev_fire_tick, delay_ticks, timer)?;
+        Ok(TimerHandle { wheel: self.id, id })
+    }
+
+    /// `schedule_with_handle` that also reports the placement. The handle is the wheel-issued
+    /// id; `TimerId`s are only ever chosen by the caller.
+    pub fn schedule_full(
+        &mut self,
+        delay_ticks: usize,
+        timer: T,
+    ) -> Result<(TimerHandle, Placement), ScheduleError> {
+        let id = self.next_id;
+        let effective_delay = self.effective_delay(delay_ticks);
+        let (level, slot) = self.schedule(delay_ticks, timer)?;
+        let handle = TimerHandle { wheel: self.id, id };
+        let placement = Placement {
+            level,
+            slot,
+            effective_delay,
+        };
+        Ok((handle, placement))
+    }
+
+    /// Schedules `timer` under an id the caller picked. An id stays taken until its timer fires
+    /// or is removed; scheduling under a taken id is rejected with `DuplicateKey`.
+    pub fn schedule_with_id(
+        &mut self,
+        id: TimerId,
+        delay_ticks: usize,
+        timer: T,
+    ) -> Result<(), ScheduleError> {
+        if self.explicit_ids.contains_key(&id) {
+            return Err(ScheduleError::DuplicateKey);
+        }
+        let handle = self.schedule_with_handle(delay_ticks, timer)?;
+        self.explicit_ids.insert(id, handle.id);
+        self.explicit_by_internal.insert(handle.id, id);
+        Ok(())
+    }
+
+    pub fn cancel(&mut self, handle: TimerHandle) -> Option<T> {
+        self.check_handle(handle);
+        let ordering_mode = self.ordering_mode;
+        let deferring = self.max_cascade_per_tick.is_some();
+        let remove = |slot: &mut VecDeque<Entry<T>>, deferred: bool| {
+            let index = slot.iter().position(|entry| entry.id == handle.id)?;
+            match ordering_mode {
+                // a deferred slot must stay sorted by remainder
+                OrderingMode::Unordered if !deferred => slot.swap_remove_back(index),
+                _ => slot.remove(index),
+            }
+        };
+        #[cfg(feature = "fast-cancel")]
+        let entry = self.rings.iter_mut().find_map(|ring| {
+            let slot = *ring.index.get(&handle.id)?;
+            let deferred = deferring && ring.level > 0 && slot == ring.cursor;
+            remove(&mut ring.slots[slot], deferred)
+        })?;
+        #[cfg(not(feature = "fast-cancel"))]
+        let entry = self.rings.iter_mut().find_map(|ring| {
+            let bits = filter_bits(handle.id);
+            let deferred = deferring && ring.level > 0;
+            let cursor = ring.cursor;
+            ring.slots
+                .iter_mut()
+                .zip(&ring.filters)
+                .enumerate()
+                .filter(|(_, (_, filter))| *filter & bits == bits)
+                .find_map(|(index, (slot, _))| remove(slot, deferred && index == cursor))
+        })?;
+        Some(self.release(entry))
+    }
+
+    pub fn cancel_id(&mut self, id: TimerId) -> Option<T> {
+        let internal = *self.explicit_ids.get(&id)?;
+        self.cancel(TimerHandle {
+            wheel: self.id,
+            id: internal,
+        })
+    }
+
+    /// Pushes the timer behind `handle` back by `additional_ticks`, moving it to the ring its new
+    /// delay needs. The timer keeps its identity, so the returned handle equals `handle`. On
+    /// error the timer is left where it was.
+    pub fn extend_deadline(
+        &mut self,
+        handle: TimerHandle,
+        additional_ticks: usize,
+    ) -> Result<TimerHandle, ExtendDeadlineError> {
+        self.check_handle(handle);
+        let (level, slot, index, due_in) =
+            self.find(handle).ok_or(ExtendDeadlineError::NotFound)?;
+        let due_in = due_in
+            .checked_add(additional_ticks)
+            .filter(|&due_in| self.exact_level(due_in).is_some())
+            .ok_or(ExtendDeadlineError::NewDelayTooLarge)?;
+        let mut entry = self.rings[level].slots[slot]
+            .remove(index)
+            .expect("located entry exists");
+        entry.requested += additional_ticks as u64;
+        self.place_exact(due_in, entry)
+            .ok()
+            .expect("new delay was checked to fit");
+        Ok(handle)
+    }
+
+    /// Brings the timer behind `handle` forward by `fewer_ticks`, moving it to the ring its new
+    /// delay needs. The timer keeps its identity, so the returned handle equals `handle`.
+    pub fn shrink_deadline(
+        &mut self,
+        handle: TimerHandle,
+        fewer_ticks: usize,
+    ) -> Result<TimerHandle, ShrinkError<T>> {
+        self.check_handle(handle);
+        let (level, slot, index, due_in) = self.find(handle).ok_or(ShrinkError::NotFound)?;
+        let mut entry = self.rings[level].slots[slot]
+            .remove(index)
+            .expect("located entry exists");
+        if fewer_ticks >= due_in {
+            return Err(ShrinkError::AlreadyDue(self.release(entry)));
+        }
+        entry.requested = entry.requested.saturating_sub(fewer_ticks as u64);
+        self.place_exact(due_in - fewer_ticks, entry)
+            .ok()
+            .expect("a shorter delay always fits");
+        Ok(handle)
+    }
+
+    /// Absolute tick the timer behind `handle` fires at, or `None` if it is no longer pending.
+    pub fn scheduled_at(&self, handle: TimerHandle) -> Option<u64> {
+        self.check_handle(handle);
+        let (_, _, _, due_in) = self.find(handle)?;
+        Some(self.current_tick + due_in as u64)
+    }
+
+    /// Ticks until the timer behind `handle` fires, for at most `max_steps` ticks, and returns it.
+    /// Other timers firing on the way are buffered for `take_buffered`, or the next `tick`.
+    pub fn tick_until(
+        &mut self,
+        handle: TimerHandle,
+        max_steps: usize,
+    ) -> Result<T, TickUntilError> {
+        self.check_handle(handle);
+        let mut diagnostics = TickDiagnostics::default();
+        let mut steps_left = max_steps;
+        while steps_left > 0 {
+            let (_, _, _, due_in) = self.find(handle).ok_or(TickUntilError::NotFound)?;
+            if self.is_paused() {
+                self.step_buffered(steps_left, &mut diagnostics);
+                break;
+            }
+            let ahead = (due_in - 1).min(steps_left);
+            self.step_buffered(ahead, &mut diagnostics);
+            steps_left -= ahead;
+            if steps_left == 0 {
+                break;
+            }
+            // a lazy cascade may have moved the timer past this step, so look it up again
+            let Some((level, slot, index)) = self.find_due_next(handle) else {
+                continue;
+            };
+            // taken out beforehand so the step cannot mix it in with the buffered timers
+            let entry = self.rings[level].slots[slot]
+                .remove(index)
+                .expect("the timer was just found there");
+            self.step_buffered(1, &mut diagnostics);
+            return Ok(self.fire_entry(entry));
+        }
+        match self.find(handle) {
+            Some((_, _, _, remaining_delay)) => {
+                Err(TickUntilError::StepsExhausted { remaining_delay })
+            }
+            None => Err(TickUntilError::NotFound),
+        }
+    }
+
+    // Steps `steps` times, buffering whatever fires.
+    fn step_buffered(&mut self, steps: usize, diagnostics: &mut TickDiagnostics) {
+        let mut fired = Vec::new();
+        for _ in 0..steps {
+            self.step(diagnostics, |timer| fired.push(timer));
+        }
+        self.undelivered.extend(fired);
+    }
+
+    // `(level, slot, index)` of the timer behind `handle` if it fires on the next step. Such a
+    // timer sits in its ring's cursor slot or the one after, so only those are searched.
+    fn find_due_next(&self, handle: TimerHandle) -> Option<(usize, usize, usize)> {
+        (0..self.rings.len()).find_map(|level| {
+            let ring = &self.rings[level];
+            let next = (ring.cursor + 1) % ring.slots.len();
+            [ring.cursor, next].into_iter().find_map(|slot| {
+                let entries = &ring.slots[slot];
+                let index = entries.iter().position(|entry| entry.id == handle.id)?;
+                (self.due_in(level, slot, entries[index].remaining) == 1)
+                    .then_some((level, slot, index))
+            })
+        })
+    }
+
+    /// Timers that fired during `tick_until` without being the one it waited for. Same as
+    /// `take_due_buffer`.
+    pub fn take_buffered(&mut self) -> Vec<T> {
+        self.take_due_buffer()
+    }
+
+    // `(level, slot, index, due_in)` of the timer behind `handle`, if it is still pending.
+    pub(crate) fn find(&self, handle: TimerHandle) -> Option<(usize, usize, usize, usize)> {
+        self.located()
+            .find(|located| located.entry.id == handle.id)
+            .map(|located| (located.level, located.slot, located.index, located.due_in))
+    }
+
+    pub(crate) fn check_handle(&self, handle: TimerHandle) {
+        assert_eq!(
+            handle.wheel, self.id,
+            "TimerHandle used with a wheel it was not issued by: {self}"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WheelBuilder;
+
+    #[test]
+    fn test_cancel() {
+        let mut wheel = HierarchicalTimingWheel::new(2, 16, 10);
+        let a = wheel.schedule_with_handle(5, "A").unwrap();
+        let b = wheel.schedule_with_handle(50, "B").unwrap();
+        wheel.schedule(5, "C").unwrap();
+
+        assert_eq!(wheel.cancel(a), Some("A"));
+        assert_eq!(wheel.cancel(a), None);
+        assert_eq!(wheel.tick(5), vec!["C"]);
+        assert_eq!(wheel.cancel(b), Some("B"));
+        assert!(wheel.tick(50).is_empty());
+    }
+
+    #[test]
+    fn test_handle_survives_graduation() {
+        let mut wheel = HierarchicalTimingWheel::new(2, 16, 10);
+        let handle = wheel.schedule_with_handle(15, "A").unwrap();
+        wheel.tick(12);
+        assert_eq!(wheel.cancel(handle), Some("A"));
+    }
+
+    #[test]
+    #[should_panic(expected = "not issued by")]
+    fn test_cancel_with_foreign_handle() {
+        let mut a = HierarchicalTimingWheel::new(1, 16, 10);
+        let mut b = HierarchicalTimingWheel::<&str>::new(1, 16, 10);
+        let handle = a.schedule_with_handle(1, "A").unwrap();
+        b.cancel(handle);
+    }
+
+    #[test]
+    fn test_schedule_with_id() {
+        let mut wheel = HierarchicalTimingWheel::new(2, 16, 10);
+        wheel.schedule_with_id(TimerId(7), 15, "A").unwrap();
+        wheel.schedule_with_id(TimerId(8), 5, "B").unwrap();
+        assert!(matches!(
+            wheel.schedule_with_id(TimerId(7), 3, "C"),
+            Err(ScheduleError::DuplicateKey)
+        ));
+
+        assert_eq!(wheel.cancel_id(TimerId(7)), Some("A"));
+        assert_eq!(wheel.cancel_id(TimerId(7)), None);
+        assert_eq!(wheel.tick(5), vec!["B"]);
+
+        // both ids are free again once their timers are gone
+        wheel.schedule_with_id(TimerId(7), 1, "D").unwrap();
+        wheel.schedule_with_id(TimerId(8), 1, "E").unwrap();
+        assert_eq!(wheel.tick(1), vec!["D", "E"]);
+    }
+
+    #[test]
+    fn test_tick_until() {
+        let mut wheel = HierarchicalTimingWheel::new(2, 16, 10);
+        wheel.schedule(3, "A").unwrap();
+        let b = wheel.schedule_with_handle(25, "B").unwrap();
+        wheel.schedule(25, "C").unwrap();
+        wheel.schedule(30, "D").unwrap();
+
+        assert_eq!(
+            wheel.tick_until(b, 10),
+            Err(TickUntilError::StepsExhausted {
+                remaining_delay: 15
+            })
+        );
+        assert_eq!(wheel.tick_until(b, 100), Ok("B"));
+        assert_eq!(wheel.now(), 25);
+        assert_eq!(wheel.take_buffered(), vec!["A", "C"]);
+        assert_eq!(wheel.tick_until(b, 100), Err(TickUntilError::NotFound));
+        assert_eq!(wheel.tick(5), vec!["D"]);
+    }
+
+    #[test]
+    fn test_tick_until_after_lazy_cascade() {
+        let mut wheel = WheelBuilder::new(3, 16, 10).with_lazy_cascade().build();
+        // graduates at tick 200 to level 1 slot 6, so it fires at 260 rather than 255
+        let a = wheel.schedule_with_handle(255, "A").unwrap();
+        wheel.schedule(257, "B").unwrap();
+
+        assert_eq!(wheel.tick_until(a, 300), Ok("A"));
+        assert_eq!(wheel.now(), 260);
+        // lazily re-placed into the same slot, so it fires alongside
+        assert_eq!(wheel.take_buffered(), vec!["B"]);
+    }
+
+    #[test]
+    fn test_cancel_after_moving_slots() {
+        let mut wheel = HierarchicalTimingWheel::new(2, 16, 10);
+        let handles: Vec<_> = (1..60)
+            .map(|delay| wheel.schedule_with_handle(delay, delay).unwrap())
+            .collect();
+        assert!(wheel.ring_mut(0).unwrap().move_timer(3, 0, 8));
+        wheel.tick(3);
+        assert_eq!(wheel.cancel(handles[2]), Some(3));
+
+        // the timers of 10..20 graduated into level 0
+        wheel.tick(7);
+        assert_eq!(wheel.cancel(handles[14]), Some(15));
+        assert_eq!(wheel.cancel(handles[40]), Some(41));
+        assert_eq!(wheel.cancel(handles[0]), None);
+    }
+
+    #[test]
+    fn test_scheduled_at() {
+        let mut wheel = HierarchicalTimingWheel::new(2, 16, 10);
+        wheel.tick(3);
+        let a = wheel.schedule_with_handle(4, "A").unwrap();
+        // aligned to level 1's rotation, so due 3 ticks before the requested tick 28
+        let b = wheel.schedule_with_handle(25, "B").unwrap();
+
+        assert_eq!(wheel.scheduled_at(a), Some(7));
+        assert_eq!(wheel.scheduled_at(b), Some(25));
+        assert_eq!(wheel.tick(4), vec!["A"]);
+        assert_eq!(wheel.scheduled_at(a), None);
+        assert_eq!(wheel.tick(18), vec!["B"]);
+    }
+
+    #[test]
+    fn test_schedule_full() {
+        let mut wheel = crate::WheelBuilder::new(2, 16, 10)
+            .with_overflow_policy(crate::OverflowPolicy::Clamp)
+            .build();
+        wheel.tick(4);
+        let (a, placement) = wheel.schedule_full(35, "A").unwrap();
+        assert_eq!(
+            placement,
+            Placement {
+                level: 1,
+                slot: 3,
+                effective_delay: 35,
+            }
+        );
+        let (_, placement) = wheel.schedule_full(500, "B").unwrap();
+        assert_eq!(placement.effective_delay, 99);
+
+        assert_eq!(wheel.cancel(a), Some("A"));
+    }
+
+    #[test]
+    fn test_extend_deadline() {
+        let mut wheel = HierarchicalTimingWheel::new(2, 16, 10);
+        wheel.tick(3);
+        let a = wheel.schedule_with_handle(4, "A").unwrap();
+
+        assert_eq!(wheel.extend_deadline(a, 30), Ok(a));
+        assert_eq!(wheel.scheduled_at(a), Some(37));
+        assert_eq!(
+            wheel.extend_deadline(a, 70),
+            Err(ExtendDeadlineError::NewDelayTooLarge)
+        );
+        assert_eq!(wheel.scheduled_at(a), Some(37));
+
+        assert!(wheel.tick(33).is_empty());
+        assert_eq!(wheel.tick(1), vec!["A"]);
+        assert_eq!(
+            wheel.extend_deadline(a, 1),
+            Err(ExtendDeadlineError::NotFound)
+        );
+    }
+
+    #[test]
+    fn test_shrink_deadline() {
+        let mut wheel = HierarchicalTimingWheel::new(2, 16, 10);
+        wheel.tick(3);
+        let a = wheel.schedule_with_handle(60, "A").unwrap();
+        let b = wheel.schedule_with_handle(5, "B").unwrap();
+
+        assert_eq!(wheel.shrink_deadline(a, 52), Ok(a));
+        assert_eq!(wheel.scheduled_at(a), Some(8));
+        assert_eq!(
+            wheel.shrink_deadline(b, 5),
+            Err(ShrinkError::AlreadyDue("B"))
+        );
+        assert_eq!(wheel.shrink_deadline(b, 1), Err(ShrinkError::NotFound));
+
+        assert!(wheel.tick(4).is_empty());
+        assert_eq!(wheel.tick(1), vec!["A"]);
+    }
+}
@@ -0,0 +1,217 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::{HierarchicalTimingWheel, ScheduleError, TimerHandle};
+
+/// What `KeyedWheel` does when a key is scheduled while a timer for it is still pending.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateKeyPolicy {
+    /// Keep the pending timer and fail with `ScheduleError::DuplicateKey`.
+    Reject,
+    /// Cancel the pending timer and schedule the new one in its place.
+    Replace,
+}
+
+/// Which path `schedule_or_update` took.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpsertOutcome {
+    /// The key had no pending timer, so a new one was scheduled.
+    Inserted,
+    /// The key's pending timer was replaced by the new timer and delay.
+    Updated,
+}
+
+pub struct KeyedWheel<K, T> {
+    wheel: HierarchicalTimingWheel<(K, T)>,
+    handles: HashMap<K, TimerHandle>,
+    policy: DuplicateKeyPolicy,
+}
+
+impl<K: Hash + Eq + Clone, T> KeyedWheel<K, T> {
+    pub fn new(
+        levels: u32,
+        slot_capacity: usize,
+        slots_per_level: usize,
+        policy: DuplicateKeyPolicy,
+    ) -> KeyedWheel<K, T> {
+        Self {
+            wheel: HierarchicalTimingWheel::new(levels, slot_capacity, slots_per_level),
+            handles: HashMap::new(),
+            policy,
+        }
+    }
+
+    pub fn schedule_keyed(
+        &mut self,
+        key: K,
+        delay_ticks: usize,
+        timer: T,
+    ) -> Result<(), ScheduleError> {
+        if self.policy == DuplicateKeyPolicy::Reject && self.handles.contains_key(&key) {
+            return Err(ScheduleError::DuplicateKey);
+        }
+        let handle = self
+            .wheel
+            .schedule_with_handle(delay_ticks, (key.clone(), timer))?;
+        if let Some(previous) = self.handles.insert(key, handle) {
+            self.wheel.cancel(previous);
+        }
+        Ok(())
+    }
+
+    /// Schedules `timer` for `key`, replacing the key's pending timer if there is one, whatever
+    /// the duplicate key policy. On error the pending timer is kept.
+    pub fn schedule_or_update(
+        &mut self,
+        key: K,
+        delay_ticks: usize,
+        timer: T,
+    ) -> Result<UpsertOutcome, ScheduleError> {
+        let handle = self
+            .wheel
+            .schedule_with_handle(delay_ticks, (key.clone(), timer))?;
+        match self.handles.insert(key, handle) {
+            Some(previous) => {
+                self.wheel.cancel(previous);
+                Ok(UpsertOutcome::Updated)
+            }
+            None => Ok(UpsertOutcome::Inserted),
+        }
+    }
+
+    pub fn schedule_many_keyed(
+        &mut self,
+        items: impl IntoIterator<Item = (K, usize, T)>,
+    ) -> Vec<Result<(), ScheduleError>> {
+        items
+            .into_iter()
+            .map(|(key, delay_ticks, timer)| self.schedule_keyed(key, delay_ticks, timer))
+            .collect()
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.handles.contains_key(key)
+    }
+
+    pub fn cancel_key(&mut self, key: &K) -> Option<T> {
+        let handle = self.handles.remove(key)?;
+        self.wheel.cancel(handle).map(|(_, timer)| timer)
+    }
+
+    pub fn tick(&mut self, steps: usize) -> Vec<(K, T)> {
+        let due = self.wheel.tick(steps);
+        for (key, _) in &due {
+            self.handles.remove(key);
+        }
+        due
+    }
+}
+
+/// Keyed wheel where setting a key always replaces its pending timer, for timeouts that get
+/// reset on activity.
+pub struct ReschedulingWheel<K, T> {
+    inner: KeyedWheel<K, T>,
+}
+
+impl<K: Hash + Eq + Clone, T> ReschedulingWheel<K, T> {
+    pub fn new(
+        levels: u32,
+        slot_capacity: usize,
+        slots_per_level: usize,
+    ) -> ReschedulingWheel<K, T> {
+        Self {
+            inner: KeyedWheel::new(
+                levels,
+                slot_capacity,
+                slots_per_level,
+                DuplicateKeyPolicy::Replace,
+            ),
+        }
+    }
+
+    /// Schedules `timer` for `key`, cancelling the key's pending timer. On error the pending
+    /// timer is kept.
+    pub fn set(&mut self, key: K, delay_ticks: usize, timer: T) -> Result<(), ScheduleError> {
+        self.inner.schedule_keyed(key, delay_ticks, timer)
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.inner.contains_key(key)
+    }
+
+    pub fn cancel_key(&mut self, key: &K) -> Option<T> {
+        self.inner.cancel_key(key)
+    }
+
+    pub fn tick(&mut self, steps: usize) -> Vec<(K, T)> {
+        self.inner.tick(steps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schedule_many_keyed_reject() {
+        let mut wheel = KeyedWheel::new(2, 16, 10, DuplicateKeyPolicy::Reject);
+        let results = wheel.schedule_many_keyed([("a", 1, 1), ("b", 2, 2), ("a", 3, 3)]);
+
+        assert!(results[0].is_ok());
+        assert!(results[1].is_ok());
+        assert!(matches!(results[2], Err(ScheduleError::DuplicateKey)));
+        assert_eq!(wheel.tick(3), vec![("a", 1), ("b", 2)]);
+    }
+
+    #[test]
+    fn test_schedule_many_keyed_replace() {
+        let mut wheel = KeyedWheel::new(2, 16, 10, DuplicateKeyPolicy::Replace);
+        let results = wheel.schedule_many_keyed([("a", 1, 1), ("b", 2, 2), ("a", 3, 3)]);
+
+        assert!(results.iter().all(Result::is_ok));
+        assert_eq!(wheel.tick(3), vec![("b", 2), ("a", 3)]);
+        assert!(!wheel.contains_key(&"a"));
+    }
+
+    #[test]
+    fn test_cancel_key() {
+        let mut wheel = KeyedWheel::new(2, 16, 10, DuplicateKeyPolicy::Reject);
+        wheel.schedule_keyed("a", 15, 1).unwrap();
+        assert_eq!(wheel.cancel_key(&"a"), Some(1));
+        assert_eq!(wheel.cancel_key(&"a"), None);
+        assert!(wheel.schedule_keyed("a", 5, 2).is_ok());
+    }
+
+    #[test]
+    fn test_schedule_or_update() {
+        let mut wheel = KeyedWheel::new(2, 16, 10, DuplicateKeyPolicy::Reject);
+        let upsert = |wheel: &mut KeyedWheel<_, _>, timer| wheel.schedule_or_update("a", 5, timer);
+        assert_eq!(upsert(&mut wheel, 1).unwrap(), UpsertOutcome::Inserted);
+        wheel.tick(3);
+        assert_eq!(upsert(&mut wheel, 2).unwrap(), UpsertOutcome::Updated);
+        assert!(matches!(
+            wheel.schedule_or_update("a", 1000, 3),
+            Err(ScheduleError::DelayTooLarge)
+        ));
+
+        assert!(wheel.tick(4).is_empty());
+        assert_eq!(wheel.tick(1), vec![("a", 2)]);
+        assert_eq!(upsert(&mut wheel, 4).unwrap(), UpsertOutcome::Inserted);
+    }
+
+    #[test]
+    fn test_rescheduling_wheel() {
+        let mut wheel = ReschedulingWheel::new(2, 16, 10);
+        wheel.set("conn-1", 5, "idle").unwrap();
+        wheel.set("conn-2", 8, "idle").unwrap();
+        wheel.tick(4);
+        wheel.set("conn-1", 5, "idle again").unwrap();
+
+        assert_eq!(wheel.tick(4), vec![("conn-2", "idle")]);
+        assert_eq!(wheel.tick(1), vec![("conn-1", "idle again")]);
+        assert!(!wheel.contains_key(&"conn-1"));
+        wheel.set("conn-3", 5, "idle").unwrap();
+        assert_eq!(wheel.cancel_key(&"conn-3"), Some("idle"));
+        assert!(wheel.tick(10).is_empty());
+    }
+}
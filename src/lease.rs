@@ -0,0 +1,106 @@
+use std::ops::Deref;
+
+use crate::{Entry, HierarchicalTimingWheel, TimerHandle};
+
+/// A pending timer taken out of its wheel for inspection, which derefs to the timer. Dropping
+/// the lease puts the timer back unchanged, due at the same tick and keeping its handle.
+pub struct Lease<'a, T> {
+    wheel: &'a mut HierarchicalTimingWheel<T>,
+    handle: TimerHandle,
+    due_in: usize,
+    // only `None` once `discard` took it
+    entry: Option<Entry<T>>,
+}
+
+impl<T> Lease<'_, T> {
+    pub fn handle(&self) -> TimerHandle {
+        self.handle
+    }
+
+    /// Puts the timer back with `new_value` as its payload, due at the same tick.
+    pub fn commit(mut self, new_value: T) -> TimerHandle {
+        if let Some(entry) = self.entry.as_mut() {
+            entry.timer = new_value;
+        }
+        self.handle
+    }
+
+    /// Cancels the timer, returning it.
+    pub fn discard(mut self) -> T {
+        let entry = self
+            .entry
+            .take()
+            .expect("lease holds its timer until dropped");
+        self.wheel.release(entry)
+    }
+}
+
+impl<T> Deref for Lease<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self
+            .entry
+            .as_ref()
+            .expect("lease holds its timer until dropped")
+            .timer
+    }
+}
+
+impl<T> Drop for Lease<'_, T> {
+    fn drop(&mut self) {
+        if let Some(entry) = self.entry.take() {
+            self.wheel
+                .place_exact(self.due_in, entry)
+                .ok()
+                .expect("a pending timer always fits the wheel it came from");
+        }
+    }
+}
+
+impl<T> HierarchicalTimingWheel<T> {
+    /// Leases the timer behind `handle`, or returns `None` if it is no longer pending.
+    pub fn lease_timer(&mut self, handle: TimerHandle) -> Option<Lease<'_, T>> {
+        self.check_handle(handle);
+        let (level, slot, index, due_in) = self.find(handle)?;
+        let entry = self.rings[level].slots[slot].remove(index);
+        Some(Lease {
+            wheel: self,
+            handle,
+            due_in,
+            entry,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lease_restores_on_drop() {
+        let mut wheel = HierarchicalTimingWheel::new(2, 16, 10);
+        let handle = wheel.schedule_with_handle(25, "A").unwrap();
+        wheel.schedule(25, "B").unwrap();
+        wheel.tick(3);
+
+        let lease = wheel.lease_timer(handle).unwrap();
+        assert_eq!(*lease, "A");
+        drop(lease);
+        assert_eq!(wheel.scheduled_at(handle), Some(25));
+        assert_eq!(wheel.tick(22), vec!["A", "B"]);
+        assert!(wheel.lease_timer(handle).is_none());
+    }
+
+    #[test]
+    fn test_lease_commit_and_discard() {
+        let mut wheel = HierarchicalTimingWheel::new(2, 16, 10);
+        let a = wheel.schedule_with_handle(5, "A").unwrap();
+        let b = wheel.schedule_with_handle(7, "B").unwrap();
+
+        let lease = wheel.lease_timer(a).unwrap();
+        assert_eq!(lease.commit("A2"), a);
+        assert_eq!(wheel.lease_timer(b).unwrap().discard(), "B");
+        assert_eq!(wheel.tick(10), vec!["A2"]);
+    }
+}
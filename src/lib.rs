@@ -1,4 +1,76 @@
-use std::collections::VecDeque;
+mod backoff;
+mod brand;
+mod builder;
+mod bulk;
+mod clock;
+mod diagnostics;
+mod diff;
+mod events;
+mod extend;
+mod future;
+mod guard;
+mod handle;
+mod keyed;
+mod lease;
+mod merge;
+#[cfg(feature = "tower")]
+mod middleware;
+mod min_heap;
+#[cfg(feature = "oneshot")]
+mod notify;
+#[cfg(feature = "rayon")]
+mod parallel;
+mod pause;
+#[cfg(feature = "rand")]
+mod periodic;
+mod relocate;
+#[cfg(feature = "smallvec")]
+mod small;
+mod steps;
+mod sync;
+#[cfg(any(test, feature = "testing"))]
+mod testing;
+mod view;
+
+use std::any::Any;
+use std::collections::{HashMap, VecDeque, vec_deque};
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{SyncSender, TrySendError};
+
+pub use backoff::{BackoffWheel, RetryInfo};
+pub use brand::{BrandedHandle, BrandedWheel};
+pub use builder::{CascadeMode, OrderingMode, OverflowPolicy, WheelBuilder};
+pub use bulk::DrainFilter;
+pub use clock::{Clock, ClockSkew, MonotonicClock, SimulatedClock, WallClockWheel, detect_skew};
+pub use diagnostics::{CascadeRecord, EarlyFirings, FIRE_HISTORY_TICKS, TickDiagnostics};
+pub use diff::WheelDiff;
+pub use events::{EventedWheel, WheelEvent};
+pub use extend::{ExtendError, TryExtend};
+pub use future::FutureWheel;
+pub use guard::WheelGuard;
+pub use handle::{
+    ExtendDeadlineError, Placement, ShrinkError, TickUntilError, TimerHandle, TimerId,
+};
+pub use keyed::{DuplicateKeyPolicy, KeyedWheel, ReschedulingWheel, UpsertOutcome};
+pub use lease::Lease;
+pub use merge::merge_due;
+#[cfg(feature = "tower")]
+pub use middleware::{TimeoutConfig, TimingWheelMiddleware};
+pub use min_heap::MinHeapWheel;
+#[cfg(feature = "oneshot")]
+pub use notify::NotifyingWheel;
+pub use pause::ResumePolicy;
+#[cfg(feature = "rand")]
+pub use periodic::JitteredWheel;
+pub use steps::{IntoIterSorted, StepIterator};
+pub use sync::{PartitionedTimingWheel, ShardedTimingWheel, SyncTimingWheel};
+#[cfg(any(test, feature = "testing"))]
+pub use testing::SimulationHarness;
+pub use view::RingView;
+
+static NEXT_WHEEL_ID: AtomicU64 = AtomicU64::new(0);
 
 pub fn hierarchical<T>(
     levels: u32,
@@ -8,10 +80,93 @@ pub fn hierarchical<T>(
     HierarchicalTimingWheel::new(levels, slot_capacity, slots_per_level)
 }
 
+/// Wheel whose pre-allocated slots take at most `bytes`: each of the `levels * slots_per_level`
+/// slots reserves `bytes / (levels * slots_per_level * size_of::<Entry<T>>())` entries, where an
+/// entry is the timer plus three words of bookkeeping. See `estimated_memory_bytes`.
+pub fn for_memory_budget<T>(
+    bytes: usize,
+    levels: u32,
+    slots_per_level: usize,
+) -> HierarchicalTimingWheel<T> {
+    let slots = (levels as usize * slots_per_level).max(1);
+    let slot_capacity = bytes / slots / size_of::<Entry<T>>().max(1);
+    HierarchicalTimingWheel::new(levels, slot_capacity, slots_per_level)
+}
+
+/// Whether a wheel with these parameters can hold any timer at all.
+pub const fn levels_valid(levels: u32, slots_per_level: usize) -> bool {
+    levels >= 1 && slots_per_level >= 2
+}
+
+/// Largest delay a wheel with these parameters accepts.
+pub const fn max_delay(levels: u32, slots_per_level: usize) -> usize {
+    slots_per_level.saturating_pow(levels).saturating_sub(1)
+}
+
+/// Same as `hierarchical`, with the geometry checked by `levels_valid` at compile time.
+pub fn hierarchical_const<T, const LEVELS: u32, const SLOTS_PER_LEVEL: usize>(
+    slot_capacity: usize,
+) -> HierarchicalTimingWheel<T> {
+    const {
+        assert!(
+            levels_valid(LEVELS, SLOTS_PER_LEVEL),
+            "invalid wheel geometry"
+        )
+    };
+    HierarchicalTimingWheel::new(LEVELS, slot_capacity, SLOTS_PER_LEVEL)
+}
+
+/// Structural parameters of a wheel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Geometry {
+    pub levels: u32,
+    pub slots_per_level: usize,
+    pub slot_capacity: usize,
+    pub max_delay: usize,
+}
+
+impl Geometry {
+    /// New, empty wheel with this geometry.
+    pub fn build<T>(&self) -> HierarchicalTimingWheel<T> {
+        HierarchicalTimingWheel::new(self.levels, self.slot_capacity, self.slots_per_level)
+    }
+}
+
+struct Entry<T> {
+    remaining: usize,
+    id: u64,
+    // Absolute tick the timer was asked to fire at.
+    requested: u64,
+    timer: T,
+}
+
+struct Located<'a, T> {
+    level: usize,
+    slot: usize,
+    index: usize,
+    due_in: usize,
+    entry: &'a Entry<T>,
+}
+
 struct Ring<T> {
     level: u32,
     cursor: usize,
-    slots: Vec<VecDeque<(usize, T)>>,
+    slots: Vec<VecDeque<Entry<T>>>,
+    // Per-slot bloom filter over the ids pushed since the slot was last emptied, so `cancel` can
+    // skip slots that cannot hold the timer. Removing a single entry leaves its bits set.
+    filters: Vec<u64>,
+    // Slot each timer was last pushed to in this ring. An entry may outlive its timer's move to
+    // another ring, so lookups confirm it against the slot; `release` drops it.
+    #[cfg(feature = "fast-cancel")]
+    index: hashbrown::HashMap<u64, usize>,
+}
+
+// Two of the 64 filter bits, picked from the top of two multiplicative hashes of `id`.
+#[inline]
+fn filter_bits(id: u64) -> u64 {
+    let a = id.wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 58;
+    let b = id.wrapping_mul(0xC2B2_AE3D_27D4_EB4F) >> 58;
+    (1 << a) | (1 << b)
 }
 
 impl<T> Ring<T> {
@@ -22,6 +177,9 @@ impl<T> Ring<T> {
             slots: (0..slots_per_level)
                 .map(|_| VecDeque::with_capacity(slot_capacity))
                 .collect(),
+            filters: vec![0; slots_per_level],
+            #[cfg(feature = "fast-cancel")]
+            index: hashbrown::HashMap::new(),
         }
     }
 
@@ -35,27 +193,104 @@ impl<T> Ring<T> {
         self.slots.len().pow(self.level + 1)
     }
 
-    fn tick(&mut self) -> Vec<(usize, T)> {
+    #[inline]
+    fn offset(&self, slot: usize) -> usize {
+        match (slot + self.slots.len() - self.cursor) % self.slots.len() {
+            0 => self.slots.len(),
+            offset => offset,
+        }
+    }
+
+    fn advance(&mut self) {
         self.cursor = (self.cursor + 1) % self.slots.len();
-        self.slots[self.cursor].drain(..).collect()
     }
 
-    fn place(&mut self, remaining: usize, timer: T) -> usize {
+    fn tick(&mut self) -> Vec<Entry<T>> {
+        self.advance();
+        self.drain_slot(self.cursor).collect()
+    }
+
+    fn place(&mut self, remaining: usize, mut entry: Entry<T>) -> usize {
         let slot_offset = remaining / self.span();
         let slot = (self.cursor + slot_offset) % self.slots.len();
-        let adjusted_remaining = remaining % self.span();
-        self.slots[slot].push_back((adjusted_remaining, timer));
+        entry.remaining = remaining % self.span();
+        self.push(slot, entry);
         slot
     }
+
+    fn push(&mut self, slot: usize, entry: Entry<T>) {
+        self.filters[slot] |= filter_bits(entry.id);
+        #[cfg(feature = "fast-cancel")]
+        self.index.insert(entry.id, slot);
+        self.slots[slot].push_back(entry);
+    }
+
+    // Empties `slot` in place, so it keeps its buffer for the timers placed there next.
+    fn drain_slot(&mut self, slot: usize) -> vec_deque::Drain<'_, Entry<T>> {
+        self.filters[slot] = 0;
+        self.slots[slot].drain(..)
+    }
 }
 
 pub struct HierarchicalTimingWheel<T> {
     rings: Vec<Ring<T>>,
+    slot_capacity: usize,
+    slots_per_level: usize,
+    overflow_policy: OverflowPolicy<T>,
+    id: u64,
+    next_id: u64,
+    pending: usize,
+    current_tick: u64,
+    undelivered: VecDeque<T>,
+    early_firings: EarlyFirings,
+    explicit_ids: HashMap<TimerId, u64>,
+    explicit_by_internal: HashMap<u64, TimerId>,
+    on_cascade: Option<Box<dyn FnMut(usize, usize) + Send + Sync>>,
+    observer: Option<events::Observer<T>>,
+    cascade_log: Option<Vec<CascadeRecord>>,
+    name: Option<String>,
+    ordering_mode: OrderingMode,
+    cascade_mode: CascadeMode,
+    // With a budget, graduating timers wait in the slot that came due, which stays the ring's
+    // cursor slot, sorted by remainder until they are re-placed or fire.
+    max_cascade_per_tick: Option<usize>,
+    min_delay_ticks: usize,
+    paused_ticks: Option<usize>,
+    fire_history: VecDeque<(u64, usize)>,
+}
+
+impl<T> fmt::Debug for HierarchicalTimingWheel<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HierarchicalTimingWheel")
+            .field("name", &self.name)
+            .field("levels", &self.rings.len())
+            .field("now", &self.current_tick)
+            .field("len", &self.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> fmt::Display for HierarchicalTimingWheel<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "wheel `{name}`"),
+            None => write!(f, "wheel #{}", self.id),
+        }
+    }
 }
 
 #[derive(Debug)]
 pub enum ScheduleError {
     DelayTooLarge,
+    DuplicateKey,
+}
+
+/// Why a `tick_safe` callback did not handle its timer.
+#[derive(Debug)]
+pub enum CallbackError<E> {
+    Failed(E),
+    /// The callback panicked; holds the panic payload.
+    Panicked(Box<dyn Any + Send>),
 }
 
 impl<T> HierarchicalTimingWheel<T> {
@@ -68,7 +303,200 @@ impl<T> HierarchicalTimingWheel<T> {
         for level in 0..levels {
             rings.push(Ring::new(level, slot_capacity, slots_per_level))
         }
-        Self { rings }
+        Self {
+            rings,
+            slot_capacity,
+            slots_per_level,
+            overflow_policy: OverflowPolicy::Error,
+            id: NEXT_WHEEL_ID.fetch_add(1, Ordering::Relaxed),
+            next_id: 0,
+            pending: 0,
+            current_tick: 0,
+            undelivered: VecDeque::new(),
+            early_firings: EarlyFirings::default(),
+            explicit_ids: HashMap::new(),
+            explicit_by_internal: HashMap::new(),
+            on_cascade: None,
+            observer: None,
+            cascade_log: None,
+            name: None,
+            ordering_mode: OrderingMode::Fifo,
+            cascade_mode: CascadeMode::Eager,
+            max_cascade_per_tick: None,
+            min_delay_ticks: 0,
+            paused_ticks: None,
+            fire_history: VecDeque::new(),
+        }
+    }
+
+    pub fn ordering_mode(&self) -> OrderingMode {
+        self.ordering_mode
+    }
+
+    pub fn cascade_mode(&self) -> CascadeMode {
+        self.cascade_mode
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn now(&self) -> u64 {
+        self.current_tick
+    }
+
+    /// Monotonic count of ticks since construction, incremented by `steps` on every
+    /// `tick(steps)` call. Same value as `now()`.
+    pub fn elapsed_ticks(&self) -> u64 {
+        self.current_tick
+    }
+
+    pub fn slot_capacity(&self) -> usize {
+        self.slot_capacity
+    }
+
+    pub const fn geometry(&self) -> Geometry {
+        let levels = self.rings.len() as u32;
+        Geometry {
+            levels,
+            slots_per_level: self.slots_per_level,
+            slot_capacity: self.slot_capacity,
+            max_delay: max_delay(levels, self.slots_per_level),
+        }
+    }
+
+    pub fn max_delay(&self) -> usize {
+        self.rings.last().map_or(0, |ring| ring.capacity() - 1)
+    }
+
+    /// Number of pending timers, kept as a counter so it costs nothing to read.
+    pub fn len(&self) -> usize {
+        self.pending
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Slot the cursor of the ring at `level` points at.
+    pub fn current_slot(&self, level: usize) -> Option<usize> {
+        self.rings.get(level).map(|ring| ring.cursor)
+    }
+
+    /// Pending timers in each ring, finest first.
+    pub fn count_by_level(&self) -> Vec<usize> {
+        self.rings
+            .iter()
+            .map(|ring| ring.slots.iter().map(VecDeque::len).sum())
+            .collect()
+    }
+
+    /// Number of ticks until the earliest pending timer fires, or `None` if nothing is pending.
+    pub fn next_expiry(&self) -> Option<usize> {
+        (0..self.rings.len())
+            .filter_map(|level| self.next_expiry_in(level))
+            .min()
+    }
+
+    /// Number of pending timers that fire at the absolute tick `future_tick`, wherever they sit
+    /// now. Ticks at or before `now()` have none.
+    pub fn count_scheduled_for_tick(&self, future_tick: u64) -> usize {
+        let Some(due_in) = future_tick
+            .checked_sub(self.current_tick)
+            .and_then(|due_in| usize::try_from(due_in).ok())
+            .filter(|&due_in| due_in > 0)
+        else {
+            return 0;
+        };
+        self.located()
+            .filter(|located| located.due_in == due_in)
+            .count()
+    }
+
+    /// Whether any pending timer fires within the next `ticks` steps. Same as comparing
+    /// `next_expiry` with `ticks`.
+    pub fn is_due_within(&self, ticks: usize) -> bool {
+        self.next_expiry().is_some_and(|expiry| expiry <= ticks)
+    }
+
+    fn next_expiry_in(&self, level: usize) -> Option<usize> {
+        let ring = &self.rings[level];
+        let slots = ring.slots.len();
+        let deferred = Some(ring.cursor).filter(|&slot| self.offset(level, slot) == 0);
+        let ahead = (1..=slots).map(|offset| (ring.cursor + offset) % slots);
+        deferred.into_iter().chain(ahead).find_map(|slot| {
+            let remaining = ring.slots[slot].iter().map(|entry| entry.remaining).min()?;
+            Some(self.due_in(level, slot, remaining))
+        })
+    }
+
+    // Ticks elapsed since the ring at `level` last advanced, given by the cursors below it.
+    fn position(&self, level: usize) -> usize {
+        self.rings[..level]
+            .iter()
+            .map(|ring| ring.cursor * ring.span())
+            .sum()
+    }
+
+    // Spans between the ring's last advance and `slot`. The cursor slot is a full rotation away,
+    // unless it holds timers deferred by the cascade budget since it came due.
+    fn offset(&self, level: usize, slot: usize) -> usize {
+        let ring = &self.rings[level];
+        if level > 0 && slot == ring.cursor && self.max_cascade_per_tick.is_some() {
+            0
+        } else {
+            ring.offset(slot)
+        }
+    }
+
+    fn due_in(&self, level: usize, slot: usize, remaining: usize) -> usize {
+        let ring = &self.rings[level];
+        self.offset(level, slot) * ring.span() + remaining - self.position(level)
+    }
+
+    // Ticks until the most recently placed timer of a slot fires.
+    pub(crate) fn last_due_in(&self, level: usize, slot: usize) -> usize {
+        let remaining = self.rings[level].slots[slot]
+            .back()
+            .map_or(0, |entry| entry.remaining);
+        self.due_in(level, slot, remaining)
+    }
+
+    // Every pending timer with its location and the number of ticks until it fires.
+    fn located(&self) -> impl Iterator<Item = Located<'_, T>> {
+        self.rings
+            .iter()
+            .enumerate()
+            .flat_map(move |(level, ring)| {
+                let position = self.position(level);
+                ring.slots
+                    .iter()
+                    .enumerate()
+                    .flat_map(move |(slot, entries)| {
+                        let base = self.offset(level, slot) * ring.span();
+                        entries
+                            .iter()
+                            .enumerate()
+                            .map(move |(index, entry)| Located {
+                                level,
+                                slot,
+                                index,
+                                due_in: base + entry.remaining - position,
+                                entry,
+                            })
+                    })
+            })
+    }
+
+    /// Removes the timer closest to firing, breaking ties by scheduling order, and returns it
+    /// with the number of ticks it had left.
+    pub fn evict_oldest(&mut self) -> Option<(usize, T)> {
+        let (due_in, level, slot, index) = self
+            .located()
+            .min_by_key(|located| (located.due_in, located.entry.id))
+            .map(|located| (located.due_in, located.level, located.slot, located.index))?;
+        let entry = self.rings[level].slots[slot].remove(index)?;
+        Some((due_in, self.release(entry)))
     }
 
     pub fn schedule(
@@ -76,49 +504,613 @@ impl<T> HierarchicalTimingWheel<T> {
         delay_ticks: usize,
         timer: T,
     ) -> Result<(usize, usize), ScheduleError> {
+        self.try_schedule(delay_ticks, timer)
+            .map_err(|(err, timer)| {
+                if let OverflowPolicy::Custom(handler) = &self.overflow_policy {
+                    handler(delay_ticks, timer);
+                }
+                err
+            })
+    }
+
+    /// Same as `schedule(1, timer).unwrap()`, without searching the rings unless a minimum delay
+    /// moves the timer past the next tick. Panics if the wheel has no levels.
+    pub fn schedule_at_next_slot(&mut self, timer: T) -> (usize, usize) {
+        if self.effective_delay(1) > 1 {
+            return self.schedule(1, timer).unwrap();
+        }
+        let entry = self.new_entry(1, timer);
+        let slot = self.rings[0].place(1, entry);
+        self.issue_id();
+        (0, slot)
+    }
+
+    /// Schedules every item, or none of them if any delay does not fit. On failure the wheel is
+    /// left untouched and the input is handed back as is.
+    #[allow(clippy::type_complexity)]
+    pub fn schedule_all_or_nothing(
+        &mut self,
+        items: Vec<(usize, T)>,
+    ) -> Result<Vec<(usize, usize)>, (ScheduleError, Vec<(usize, T)>)> {
+        if items
+            .iter()
+            .any(|(delay_ticks, _)| !self.fits(self.effective_delay(*delay_ticks)))
+        {
+            return Err((ScheduleError::DelayTooLarge, items));
+        }
+        Ok(items
+            .into_iter()
+            .map(|(delay_ticks, timer)| {
+                self.try_schedule(delay_ticks, timer)
+                    .ok()
+                    .expect("delay was checked to fit")
+            })
+            .collect())
+    }
+
+    /// Schedules `timer` to fire exactly `delay_ticks` after `prev_fire_tick` rather than after
+    /// `now()`, so a chain of timers does not drift by the time spent handling each link. A
+    /// target at or before `now()` fires on the next tick.
+    pub fn schedule_chained(
+        &mut self,
+        prev_fire_tick: u64,
+        delay_ticks: usize,
+        timer: T,
+    ) -> Result<(usize, usize), ScheduleError> {
+        let target = prev_fire_tick.saturating_add(delay_ticks as u64);
+        let delay_ticks = usize::try_from(target.saturating_sub(self.current_tick))
+            .map_err(|_| ScheduleError::DelayTooLarge)?;
+        let entry = self.new_entry(delay_ticks, timer);
+        let placement = self
+            .place_exact(delay_ticks, entry)
+            .map_err(|_| ScheduleError::DelayTooLarge)?;
+        self.issue_id();
+        Ok(placement)
+    }
+
+    /// Schedules `timer` to fire exactly at the next multiple of `period` after `now()`, so
+    /// timers sharing a period fire in lockstep whenever they were scheduled. Panics if `period`
+    /// is zero.
+    pub fn schedule_aligned(
+        &mut self,
+        period: usize,
+        timer: T,
+    ) -> Result<(usize, usize), ScheduleError> {
+        assert!(period > 0, "period must be non-zero");
+        let now = self.current_tick;
+        let delay_ticks = period - (now % period as u64) as usize;
+        self.schedule_chained(now, delay_ticks, timer)
+    }
+
+    /// Schedules timers stored with the absolute tick they are due at. Deadlines at or before
+    /// `now()` fire on the next tick. Timers that do not fit are returned with their deadline.
+    pub fn load_deadlines(&mut self, items: impl IntoIterator<Item = (u64, T)>) -> Vec<(u64, T)> {
+        let mut rejected = Vec::new();
+        for (deadline, timer) in items {
+            let delay_ticks = deadline.saturating_sub(self.current_tick);
+            let Ok(delay_ticks) = usize::try_from(delay_ticks) else {
+                rejected.push((deadline, timer));
+                continue;
+            };
+            if let Err((_, timer)) = self.try_schedule(delay_ticks, timer) {
+                rejected.push((deadline, timer));
+            }
+        }
+        rejected
+    }
+
+    fn fits(&self, delay_ticks: usize) -> bool {
+        delay_ticks.max(1) <= self.max_delay()
+    }
+
+    // Delay used for placement once the overflow policy and the delay floor have been applied.
+    fn effective_delay(&self, delay_ticks: usize) -> usize {
+        let max_delay = self.max_delay();
+        let delay_ticks = if max_delay == 0 || self.fits(delay_ticks) {
+            delay_ticks
+        } else {
+            match self.overflow_policy {
+                OverflowPolicy::Clamp => max_delay,
+                OverflowPolicy::Wrap => delay_ticks % max_delay,
+                OverflowPolicy::Error | OverflowPolicy::Custom(_) => delay_ticks,
+            }
+        };
+        delay_ticks.max(self.min_delay_ticks)
+    }
+
+    // Like `schedule`, but hands the timer back when it could not be placed.
+    fn try_schedule(
+        &mut self,
+        delay_ticks: usize,
+        timer: T,
+    ) -> Result<(usize, usize), (ScheduleError, T)> {
+        let delay_ticks = self.effective_delay(delay_ticks);
+        let entry = self.new_entry(delay_ticks, timer);
+        let placement = self
+            .place(delay_ticks, entry)
+            .map_err(|entry| (ScheduleError::DelayTooLarge, entry.timer))?;
+        self.issue_id();
+        Ok(placement)
+    }
+
+    // Entry for a new timer due in `delay_ticks`, using the next id without consuming it.
+    fn new_entry(&self, delay_ticks: usize, timer: T) -> Entry<T> {
+        Entry {
+            remaining: 0,
+            id: self.next_id,
+            requested: self.current_tick + delay_ticks.max(1) as u64,
+            timer,
+        }
+    }
+
+    // Consumes the id of an entry that was just placed, counting it as pending.
+    fn issue_id(&mut self) {
+        self.next_id += 1;
+        self.pending += 1;
+    }
+
+    fn place(&mut self, delay_ticks: usize, entry: Entry<T>) -> Result<(usize, usize), Entry<T>> {
         let delay_ticks = (delay_ticks == 0) as usize | delay_ticks;
         for (level, ring) in self.rings.iter_mut().enumerate() {
             if delay_ticks < ring.capacity() && delay_ticks >= ring.span() {
-                let slot = ring.place(delay_ticks, timer);
+                let slot = ring.place(delay_ticks, entry);
                 return Ok((level, slot));
             }
         }
-        Err(ScheduleError::DelayTooLarge)
+        Err(entry)
+    }
+
+    fn fire_entry(&mut self, entry: Entry<T>) -> T {
+        if entry.requested > self.current_tick {
+            self.early_firings.timers += 1;
+            self.early_firings.ticks += entry.requested - self.current_tick;
+        }
+        self.record_fire();
+        self.release(entry)
     }
 
+    // Bookkeeping for an entry leaving the wheel, whether it fired or was removed.
+    fn release(&mut self, entry: Entry<T>) -> T {
+        self.pending -= 1;
+        if !self.explicit_ids.is_empty()
+            && let Some(id) = self.explicit_by_internal.remove(&entry.id)
+        {
+            self.explicit_ids.remove(&id);
+        }
+        #[cfg(feature = "fast-cancel")]
+        for ring in &mut self.rings {
+            ring.index.remove(&entry.id);
+        }
+        entry.timer
+    }
+
+    /// Advances the wheel `steps` ticks and returns the timers that came due, after any left in
+    /// the due buffer. Timers are ordered by the tick they fired at, and within a tick by the order
+    /// they were scheduled in, whichever ring they were held in. Rescheduling a pending timer
+    /// with `extend_deadline` or `shrink_deadline` keeps its place in that order.
     pub fn tick(&mut self, steps: usize) -> Vec<T> {
-        let mut due = Vec::new();
+        let mut due: Vec<T> = self.undelivered.drain(..).collect();
+        let mut diagnostics = TickDiagnostics::default();
+        for _ in 0..steps {
+            self.step(&mut diagnostics, |timer| due.push(timer));
+        }
+        due
+    }
+
+    /// Same as `tick(1)`, for event loops that advance one tick at a time. A step on which ring 0
+    /// does not wrap cannot cascade, so it only drains ring 0's next slot, skipping the per-ring
+    /// loop and the cascade. Wrapping, paused and budgeted steps take the full step.
+    #[inline(always)]
+    pub fn tick_single_step(&mut self) -> Vec<T> {
+        let mut due: Vec<T> = self.undelivered.drain(..).collect();
+        let ring = &mut self.rings[0];
+        if self.paused_ticks.is_some()
+            || self.max_cascade_per_tick.is_some()
+            || ring.cursor + 1 == ring.slots.len()
+        {
+            self.step(&mut TickDiagnostics::default(), |timer| due.push(timer));
+            return due;
+        }
+        self.current_tick += 1;
+        ring.advance();
+        let cursor = ring.cursor;
+        let mut entries: Vec<Entry<T>> = ring.drain_slot(cursor).collect();
+        entries.sort_unstable_by_key(|entry| entry.id);
+        due.reserve(entries.len());
+        for entry in entries {
+            let timer = self.fire_entry(entry);
+            due.push(timer);
+        }
+        due
+    }
+
+    /// Like `tick`, but hands each fired timer to `f` instead of collecting them.
+    pub fn tick_fn<F: FnMut(T)>(&mut self, steps: usize, mut f: F) {
+        while let Some(timer) = self.undelivered.pop_front() {
+            f(timer);
+        }
+        let mut diagnostics = TickDiagnostics::default();
+        for _ in 0..steps {
+            self.step(&mut diagnostics, &mut f);
+        }
+    }
+
+    /// Like `tick_fn`, but keeps going when `f` fails or panics on a timer. Returns how many
+    /// timers `f` handled, and every other timer with the error or panic it caused. `f` borrows
+    /// each timer so that failed ones can be handed back.
+    pub fn tick_safe<E, F: FnMut(&T) -> Result<(), E>>(
+        &mut self,
+        steps: usize,
+        mut f: F,
+    ) -> (usize, Vec<(T, CallbackError<E>)>) {
+        let mut handled = 0;
+        let mut failed = Vec::new();
+        self.tick_fn(steps, |timer| {
+            match panic::catch_unwind(AssertUnwindSafe(|| f(&timer))) {
+                Ok(Ok(())) => handled += 1,
+                Ok(Err(err)) => failed.push((timer, CallbackError::Failed(err))),
+                Err(payload) => failed.push((timer, CallbackError::Panicked(payload))),
+            }
+        });
+        (handled, failed)
+    }
+
+    /// Ticks up to `max_steps` times, stopping as soon as `max_timers` timers were collected.
+    /// Timers beyond `max_timers` that fired on the last tick are kept in the due buffer and
+    /// returned first by the next call, or the next `tick`.
+    pub fn tick_n_returning_first_n(&mut self, max_steps: usize, max_timers: usize) -> Vec<T> {
+        let buffered = self.undelivered.len().min(max_timers);
+        let mut due: Vec<T> = self.undelivered.drain(..buffered).collect();
+        let mut diagnostics = TickDiagnostics::default();
+        for _ in 0..max_steps {
+            if due.len() >= max_timers {
+                break;
+            }
+            let mut fired = Vec::new();
+            self.step(&mut diagnostics, |timer| fired.push(timer));
+            let excess = fired.split_off(fired.len().min(max_timers - due.len()));
+            due.extend(fired);
+            self.undelivered.extend(excess);
+        }
+        due
+    }
+
+    /// Like `tick`, but never advances past `max_tick`; check `now()` for how far it got.
+    pub fn tick_capped(&mut self, steps: usize, max_tick: u64) -> Vec<T> {
+        let room = max_tick.saturating_sub(self.current_tick);
+        let steps = usize::try_from(room).map_or(steps, |room| steps.min(room));
+        self.tick(steps)
+    }
+
+    pub fn tick_timestamped(&mut self, steps: usize) -> Vec<(u64, T)> {
+        let now = self.current_tick;
+        let mut due: Vec<(u64, T)> = self.undelivered.drain(..).map(|t| (now, t)).collect();
+        let mut diagnostics = TickDiagnostics::default();
         for _ in 0..steps {
-            let mut graduated = Vec::new();
-            let mut i = 0;
-            let mut inner_ticked = false;
-            loop {
-                let should_tick = i == 0 || (inner_ticked && self.rings[i - 1].cursor == 0);
-                if should_tick {
-                    let ring = &mut self.rings[i];
+            let tick = self.current_tick + 1;
+            self.step(&mut diagnostics, |timer| due.push((tick, timer)));
+        }
+        due
+    }
+
+    /// Like `tick_timestamped`, but groups the timers of each tick into one batch. Ticks at
+    /// which nothing fired are skipped.
+    pub fn tick_partitioned(&mut self, steps: usize) -> impl Iterator<Item = (u64, Vec<T>)> {
+        let mut batches: Vec<(u64, Vec<T>)> = Vec::new();
+        for (tick, timer) in self.tick_timestamped(steps) {
+            match batches.last_mut() {
+                Some((last, batch)) if *last == tick => batch.push(timer),
+                _ => batches.push((tick, vec![timer])),
+            }
+        }
+        batches.into_iter()
+    }
+
+    fn step(&mut self, diagnostics: &mut TickDiagnostics, mut fire: impl FnMut(T)) {
+        if let Some(paused) = self.paused_ticks.as_mut() {
+            *paused += 1;
+            return;
+        }
+        self.current_tick += 1;
+        diagnostics.steps_taken += 1;
+        let mut due = Vec::new();
+        let mut graduated = Vec::new();
+        let mut i = 0;
+        let mut inner_ticked = false;
+        loop {
+            let should_tick = i == 0 || (inner_ticked && self.rings[i - 1].cursor == 0);
+            if should_tick {
+                diagnostics.rings_ticked += 1;
+                let ring = &mut self.rings[i];
+                if i > 0 && self.max_cascade_per_tick.is_some() {
+                    ring.advance();
+                    let slot = &mut ring.slots[ring.cursor];
+                    slot.make_contiguous()
+                        .sort_by_key(|entry| (entry.remaining, entry.id));
+                    diagnostics.graduations += slot.len();
+                    if !slot.is_empty()
+                        && let Some(on_cascade) = self.on_cascade.as_mut()
+                    {
+                        on_cascade(i, slot.len());
+                    }
+                } else {
                     let timers = ring.tick();
                     if i == 0 {
-                        due.extend(timers.into_iter().map(|(_, t)| t));
+                        due.extend(timers);
                     } else {
-                        graduated.extend(timers);
+                        if !timers.is_empty()
+                            && let Some(on_cascade) = self.on_cascade.as_mut()
+                        {
+                            on_cascade(i, timers.len());
+                        }
+                        graduated.extend(timers.into_iter().map(|entry| (i, entry)));
                     }
                 }
-                inner_ticked = should_tick;
-                i += 1;
-                if i == self.rings.len() {
+            }
+            inner_ticked = should_tick;
+            i += 1;
+            if i == self.rings.len() {
+                break;
+            }
+        }
+        diagnostics.graduations += graduated.len();
+        match self.max_cascade_per_tick {
+            Some(budget) => self.cascade_within_budget(budget, &mut due, diagnostics),
+            None => self.cascade(graduated, &mut due, diagnostics),
+        }
+        // ids follow schedule order, so the due set does not depend on which ring held a timer
+        due.sort_unstable_by_key(|entry| entry.id);
+        for entry in due {
+            let timer = self.fire_entry(entry);
+            fire(timer);
+        }
+    }
+
+    // Fires graduated timers with nothing left and re-places the others per the cascade mode.
+    fn cascade(
+        &mut self,
+        graduated: Vec<(usize, Entry<T>)>,
+        due: &mut Vec<Entry<T>>,
+        diagnostics: &mut TickDiagnostics,
+    ) {
+        for (level, mut entry) in graduated {
+            if entry.remaining == 0 {
+                due.push(entry);
+            } else if self.cascade_mode == CascadeMode::Lazy {
+                diagnostics.reschedules += 1;
+                // the rings below just wrapped, so slot k of the one below is k spans away
+                let below = &mut self.rings[level - 1];
+                let slot =
+                    (below.cursor + entry.remaining.div_ceil(below.span())) % below.slots.len();
+                entry.remaining = 0;
+                below.push(slot, entry);
+                self.report_graduation(level, (level - 1, slot));
+            } else {
+                diagnostics.reschedules += 1;
+                debug_assert!(
+                    self.fits(entry.remaining),
+                    "graduated remainder {} does not fit the wheel",
+                    entry.remaining
+                );
+                let placement = self
+                    .place(entry.remaining, entry)
+                    .ok()
+                    .expect("graduation overflow: bug in timing wheel arithmetic");
+                self.report_graduation(level, placement);
+            }
+        }
+    }
+
+    // Fires the timers deferred in each ring's cursor slot as they come due, then re-places at
+    // most `budget` of the others, earliest first. A deferred timer's remainder counts from when
+    // its slot came due, so none fires late.
+    fn cascade_within_budget(
+        &mut self,
+        budget: usize,
+        due: &mut Vec<Entry<T>>,
+        diagnostics: &mut TickDiagnostics,
+    ) {
+        for level in 1..self.rings.len() {
+            let position = self.position(level);
+            let ring = &mut self.rings[level];
+            let slot = &mut ring.slots[ring.cursor];
+            while slot
+                .front()
+                .is_some_and(|entry| entry.remaining <= position)
+            {
+                due.extend(slot.pop_front());
+            }
+        }
+        for _ in 0..budget {
+            let Some((due_in, _, level)) = (1..self.rings.len())
+                .filter_map(|level| {
+                    let ring = &self.rings[level];
+                    let entry = ring.slots[ring.cursor].front()?;
+                    Some((
+                        self.due_in(level, ring.cursor, entry.remaining),
+                        entry.id,
+                        level,
+                    ))
+                })
+                .min()
+            else {
+                break;
+            };
+            let ring = &mut self.rings[level];
+            let entry = ring.slots[ring.cursor]
+                .pop_front()
+                .expect("the deferred slot was just checked");
+            diagnostics.reschedules += 1;
+            let placement = self
+                .place_exact(due_in, entry)
+                .ok()
+                .expect("a graduated remainder always fits below its ring");
+            self.report_graduation(level, placement);
+        }
+        for ring in &mut self.rings[1..] {
+            if ring.slots[ring.cursor].is_empty() {
+                ring.filters[ring.cursor] = 0;
+            }
+        }
+    }
+
+    /// Advances the wheel like `tick` and sends due timers to `tx` until the channel is full.
+    /// Timers that did not fit are kept, in order, and delivered first by the next tick.
+    /// Returns the number of timers sent, or the first timer rejected by a disconnected channel.
+    pub fn tick_to_channel(
+        &mut self,
+        steps: usize,
+        tx: &SyncSender<T>,
+    ) -> Result<usize, TrySendError<T>> {
+        let due = self.tick(steps);
+        self.undelivered.extend(due);
+        let mut sent = 0;
+        while let Some(timer) = self.undelivered.pop_front() {
+            match tx.try_send(timer) {
+                Ok(()) => sent += 1,
+                Err(TrySendError::Full(timer)) => {
+                    self.undelivered.push_front(timer);
                     break;
                 }
+                Err(err) => return Err(err),
             }
-            for (remaining_delay, timer) in graduated {
-                if remaining_delay == 0 {
-                    due.push(timer);
-                } else {
-                    self.schedule(remaining_delay, timer).unwrap();
-                }
+        }
+        Ok(sent)
+    }
+
+    /// Returns what is due at the current tick without advancing: timers a previous tick could
+    /// not deliver, then anything left in ring 0's cursor slot. `tick` delivers the same timers
+    /// before it advances, so draining first only separates consuming from advancing.
+    pub fn drain_due_now(&mut self) -> Vec<T> {
+        let mut due: Vec<T> = self.undelivered.drain(..).collect();
+        if let Some(ring) = self.rings.first_mut() {
+            let cursor = ring.cursor;
+            let entries: Vec<_> = ring.drain_slot(cursor).collect();
+            for entry in entries {
+                let timer = self.fire_entry(entry);
+                due.push(timer);
             }
         }
         due
     }
+
+    /// Ticks `steps` times, adding the fired timers to the due buffer instead of returning them.
+    /// The buffer is the one `tick_to_channel` and `tick_until` leave timers in, and grows by
+    /// every fired timer until it is taken, or emptied by the next `tick`.
+    pub fn advance(&mut self, steps: usize) {
+        let mut diagnostics = TickDiagnostics::default();
+        let mut fired = Vec::new();
+        for _ in 0..steps {
+            self.step(&mut diagnostics, |timer| fired.push(timer));
+        }
+        self.undelivered.extend(fired);
+    }
+
+    /// Empties the due buffer, returning the timers in the order they fired.
+    pub fn take_due_buffer(&mut self) -> Vec<T> {
+        self.undelivered.drain(..).collect()
+    }
+
+    pub fn undelivered(&self) -> usize {
+        self.undelivered.len()
+    }
+
+    /// Reserves room for at least `capacity` more timers in every slot of every ring.
+    pub fn preallocate_all_slots(&mut self, capacity: usize) {
+        for slot in self.rings.iter_mut().flat_map(|ring| ring.slots.iter_mut()) {
+            slot.reserve(capacity);
+        }
+    }
+
+    /// Grows or shrinks every slot to hold `new_capacity` timers without reallocating, never
+    /// dropping below what a slot currently holds. Becomes the wheel's `slot_capacity`.
+    pub fn set_slot_capacity(&mut self, new_capacity: usize) {
+        for slot in self.rings.iter_mut().flat_map(|ring| ring.slots.iter_mut()) {
+            if slot.capacity() < new_capacity {
+                slot.reserve(new_capacity - slot.len());
+            } else {
+                slot.shrink_to(new_capacity);
+            }
+        }
+        self.slot_capacity = new_capacity;
+    }
+
+    /// Releases spare slot capacity in the ring at `level` only, leaving the other rings sized.
+    pub fn shrink_level(&mut self, level: usize) {
+        self.check_level(level);
+        for slot in self.rings[level].slots.iter_mut() {
+            slot.shrink_to_fit();
+        }
+    }
+
+    /// Timers the slots of the ring at `level` can hold without reallocating.
+    pub fn capacity_of_level(&self, level: usize) -> usize {
+        self.check_level(level);
+        self.rings[level].slots.iter().map(VecDeque::capacity).sum()
+    }
+
+    /// Pending timers per configured slot capacity, over every slot of every ring. Above 1.0,
+    /// slots hold more than they were sized for on average, so some have had to reallocate.
+    /// A wheel with no slot capacity, such as one `for_memory_budget` had no room for, reports
+    /// 0.0 while empty and `f64::INFINITY` once it holds a timer.
+    pub fn slot_load_factor(&self) -> f64 {
+        let configured = self.rings.len() * self.slots_per_level * self.slot_capacity;
+        match (self.len(), configured) {
+            (0, _) => 0.0,
+            (_, 0) => f64::INFINITY,
+            (pending, configured) => pending as f64 / configured as f64,
+        }
+    }
+
+    /// Bytes currently reserved for timers across all slots.
+    pub fn estimated_memory_bytes(&self) -> usize {
+        self.total_capacity() * size_of::<Entry<T>>()
+    }
+
+    pub fn total_capacity(&self) -> usize {
+        (0..self.rings.len())
+            .map(|level| self.capacity_of_level(level))
+            .sum()
+    }
+
+    fn check_level(&self, level: usize) {
+        assert!(
+            level < self.rings.len(),
+            "level {level} out of range for a wheel with {} levels",
+            self.rings.len()
+        );
+    }
+
+    /// `(level, slot)` of every slot currently holding at least one timer. Reads one filter
+    /// word per slot and only looks into slots whose filter is set. A filter stays set after
+    /// single removals until its slot empties, so such slots are confirmed before they count.
+    pub fn slots_with_timers(&self) -> Vec<(usize, usize)> {
+        self.rings
+            .iter()
+            .enumerate()
+            .flat_map(|(level, ring)| {
+                ring.filters
+                    .iter()
+                    .enumerate()
+                    .filter(|&(slot, &filter)| filter != 0 && !ring.slots[slot].is_empty())
+                    .map(move |(slot, _)| (level, slot))
+            })
+            .collect()
+    }
+
+    pub fn peek_at_slot(&self, level: usize, slot: usize) -> Option<Vec<&T>> {
+        let slot = self.rings.get(level)?.slots.get(slot)?;
+        Some(slot.iter().map(|entry| &entry.timer).collect())
+    }
+
+    pub fn peek_entries_at_slot(&self, level: usize, slot: usize) -> Option<Vec<(usize, &T)>> {
+        let slot = self.rings.get(level)?.slots.get(slot)?;
+        Some(
+            slot.iter()
+                .map(|entry| (entry.remaining, &entry.timer))
+                .collect(),
+        )
+    }
 }
 
 #[cfg(test)]
@@ -170,6 +1162,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::single_match)]
     fn test_overflow() {
         let mut timing_wheel = HierarchicalTimingWheel::new(1, 16, 10);
         match timing_wheel.schedule(10, "X") {
@@ -221,4 +1214,593 @@ mod tests {
         let timers = wheel.tick(1);
         assert_eq!(timers, vec!["L4"]);
     }
+
+    #[test]
+    fn test_tick_to_channel_backpressure() {
+        let mut wheel = HierarchicalTimingWheel::new(1, 16, 10);
+        for timer in 0..5 {
+            wheel.schedule(1, timer).unwrap();
+        }
+        let (tx, rx) = std::sync::mpsc::sync_channel(2);
+
+        assert_eq!(wheel.tick_to_channel(1, &tx).unwrap(), 2);
+        assert_eq!(wheel.undelivered(), 3);
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(), vec![0, 1]);
+
+        assert_eq!(wheel.tick_to_channel(1, &tx).unwrap(), 2);
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(), vec![2, 3]);
+
+        assert_eq!(wheel.tick(1), vec![4]);
+        assert_eq!(wheel.undelivered(), 0);
+    }
+
+    #[test]
+    fn test_peek_at_slot() {
+        let mut wheel = HierarchicalTimingWheel::new(2, 16, 10);
+        wheel.schedule(3, "A").unwrap();
+        wheel.schedule(3, "B").unwrap();
+        wheel.schedule(25, "C").unwrap();
+
+        assert_eq!(wheel.peek_at_slot(0, 3), Some(vec![&"A", &"B"]));
+        assert_eq!(wheel.peek_at_slot(0, 4), Some(vec![]));
+        assert_eq!(wheel.peek_entries_at_slot(1, 2), Some(vec![(5, &"C")]));
+        assert_eq!(wheel.peek_at_slot(0, 10), None);
+        assert_eq!(wheel.peek_entries_at_slot(2, 0), None);
+
+        assert_eq!(wheel.tick(3), vec!["A", "B"]);
+    }
+
+    #[test]
+    fn test_elapsed_ticks() {
+        let mut wheel = HierarchicalTimingWheel::<()>::new(2, 16, 10);
+        assert_eq!(wheel.elapsed_ticks(), 0);
+        wheel.tick(3);
+        wheel.tick(0);
+        wheel.tick(12);
+        assert_eq!(wheel.elapsed_ticks(), 15);
+        assert_eq!(wheel.now(), 15);
+    }
+
+    #[test]
+    fn test_tick_timestamped() {
+        let mut wheel = HierarchicalTimingWheel::new(2, 16, 10);
+        wheel.schedule(2, "A").unwrap();
+        wheel.schedule(12, "B").unwrap();
+        wheel.schedule(2, "C").unwrap();
+
+        assert_eq!(wheel.tick_timestamped(5), vec![(2, "A"), (2, "C")]);
+        assert_eq!(wheel.tick_timestamped(10), vec![(12, "B")]);
+    }
+
+    #[test]
+    fn test_next_expiry() {
+        let mut wheel = HierarchicalTimingWheel::new(3, 16, 10);
+        assert_eq!(wheel.next_expiry(), None);
+
+        wheel.schedule(250, "A").unwrap();
+        assert_eq!(wheel.next_expiry(), Some(250));
+        wheel.schedule(37, "B").unwrap();
+        assert_eq!(wheel.next_expiry(), Some(37));
+
+        // level 1 slots are aligned to the level's rotation, not to the schedule time
+        wheel.tick(5);
+        wheel.schedule(10, "C").unwrap();
+        assert_eq!(wheel.next_expiry(), Some(5));
+        assert_eq!(wheel.tick(5), vec!["C"]);
+        assert_eq!(wheel.next_expiry(), Some(27));
+        assert_eq!(wheel.len(), 2);
+    }
+
+    #[test]
+    fn test_is_due_within() {
+        let mut wheel = HierarchicalTimingWheel::new(3, 16, 10);
+        assert!(!wheel.is_due_within(1000));
+
+        wheel.schedule(25, "A").unwrap();
+        assert!(wheel.is_due_within(25));
+        assert!(!wheel.is_due_within(24));
+
+        wheel.schedule(5, "B").unwrap();
+        assert!(wheel.is_due_within(5));
+        assert!(!wheel.is_due_within(4));
+
+        let mut wheel = WheelBuilder::new(2, 16, 10)
+            .with_max_cascade_per_tick(0)
+            .build();
+        wheel.schedule(15, "C").unwrap();
+        wheel.tick(10);
+        assert_eq!(wheel.next_expiry(), Some(5));
+        assert!(wheel.is_due_within(5));
+        assert!(!wheel.is_due_within(4));
+    }
+
+    #[test]
+    fn test_schedule_at_next_slot() {
+        let mut wheel = HierarchicalTimingWheel::new(2, 16, 10);
+        assert_eq!(wheel.schedule_at_next_slot("A"), (0, 1));
+        wheel.tick(9);
+        assert_eq!(wheel.schedule_at_next_slot("B"), (0, 0));
+        assert_eq!(wheel.schedule(1, "C").unwrap(), (0, 0));
+        assert_eq!(wheel.tick(1), vec!["B", "C"]);
+    }
+
+    #[test]
+    fn test_evict_oldest() {
+        let mut wheel = HierarchicalTimingWheel::new(3, 16, 10);
+        wheel.schedule(150, "A").unwrap();
+        wheel.schedule(7, "B").unwrap();
+        wheel.schedule(12, "C").unwrap();
+        wheel.tick(5);
+        wheel.schedule(2, "D").unwrap();
+
+        assert_eq!(wheel.evict_oldest(), Some((2, "B")));
+        assert_eq!(wheel.evict_oldest(), Some((2, "D")));
+        assert_eq!(wheel.evict_oldest(), Some((7, "C")));
+        assert_eq!(wheel.evict_oldest(), Some((145, "A")));
+        assert_eq!(wheel.evict_oldest(), None);
+        assert!(wheel.tick(200).is_empty());
+    }
+
+    #[test]
+    fn test_schedule_all_or_nothing() {
+        let mut wheel = HierarchicalTimingWheel::new(2, 16, 10);
+        wheel.schedule(5, "X").unwrap();
+
+        let (err, items) = wheel
+            .schedule_all_or_nothing(vec![(1, "A"), (50, "B"), (100, "C")])
+            .unwrap_err();
+        assert!(matches!(err, ScheduleError::DelayTooLarge));
+        assert_eq!(items, vec![(1, "A"), (50, "B"), (100, "C")]);
+        assert_eq!(wheel.len(), 1);
+
+        let placements = wheel
+            .schedule_all_or_nothing(vec![(1, "A"), (50, "B"), (0, "C")])
+            .unwrap();
+        assert_eq!(placements, vec![(0, 1), (1, 5), (0, 1)]);
+        assert_eq!(wheel.tick(5), vec!["A", "C", "X"]);
+    }
+
+    #[test]
+    fn test_slot_capacity() {
+        let wheel = HierarchicalTimingWheel::<()>::new(2, 16, 10);
+        assert_eq!(wheel.slot_capacity(), 16);
+    }
+
+    #[test]
+    fn test_slots_with_timers() {
+        let mut wheel = HierarchicalTimingWheel::new(3, 16, 10);
+        assert!(wheel.slots_with_timers().is_empty());
+
+        wheel.schedule(3, "A").unwrap();
+        wheel.schedule(3, "B").unwrap();
+        let c = wheel.schedule_with_handle(42, "C").unwrap();
+        wheel.schedule(500, "D").unwrap();
+        assert_eq!(wheel.slots_with_timers(), vec![(0, 3), (1, 4), (2, 5)]);
+
+        wheel.tick(3);
+        assert_eq!(wheel.slots_with_timers(), vec![(1, 4), (2, 5)]);
+        // cancelling leaves the slot's filter set, but the slot no longer counts
+        wheel.cancel(c);
+        assert_ne!(wheel.rings[1].filters[4], 0);
+        assert_eq!(wheel.slots_with_timers(), vec![(2, 5)]);
+    }
+
+    #[test]
+    fn test_preallocate_all_slots() {
+        let mut wheel = HierarchicalTimingWheel::<()>::new(2, 0, 10);
+        wheel.preallocate_all_slots(32);
+        let capacities: Vec<usize> = wheel
+            .rings
+            .iter()
+            .flat_map(|ring| ring.slots.iter().map(VecDeque::capacity))
+            .collect();
+        assert_eq!(capacities.len(), 20);
+        assert!(capacities.iter().all(|capacity| *capacity >= 32));
+    }
+
+    #[test]
+    fn test_tick_capped_stops_at_ceiling() {
+        let mut wheel = HierarchicalTimingWheel::new(2, 16, 10);
+        wheel.schedule(3, "A").unwrap();
+        wheel.schedule(8, "B").unwrap();
+
+        assert_eq!(wheel.tick_capped(100, 5), vec!["A"]);
+        assert_eq!(wheel.now(), 5);
+        assert!(wheel.tick_capped(100, 5).is_empty());
+        assert_eq!(wheel.now(), 5);
+        assert_eq!(wheel.tick_capped(2, 50), Vec::<&str>::new());
+        assert_eq!(wheel.now(), 7);
+        assert_eq!(wheel.tick_capped(100, 8), vec!["B"]);
+    }
+
+    #[test]
+    fn test_tick_fn() {
+        let mut wheel = HierarchicalTimingWheel::new(2, 16, 10);
+        wheel.schedule(15, "C").unwrap();
+        wheel.schedule(2, "A").unwrap();
+        wheel.schedule(2, "B").unwrap();
+
+        let mut fired = Vec::new();
+        wheel.tick_fn(20, |timer| fired.push(timer));
+        assert_eq!(fired, ["A", "B", "C"]);
+        assert!(wheel.is_empty());
+    }
+
+    #[test]
+    fn test_drain_due_now_interleaved_with_tick() {
+        let mut wheel = HierarchicalTimingWheel::new(2, 16, 10);
+        wheel.schedule(2, "A").unwrap();
+        wheel.schedule(2, "B").unwrap();
+        wheel.schedule(3, "C").unwrap();
+        let (tx, rx) = std::sync::mpsc::sync_channel(1);
+        let capacity = |wheel: &HierarchicalTimingWheel<_>| -> usize {
+            wheel.rings[0].slots.iter().map(VecDeque::capacity).sum()
+        };
+        let before = capacity(&wheel);
+
+        assert_eq!(wheel.tick_to_channel(2, &tx).unwrap(), 1);
+        assert_eq!(rx.try_recv(), Ok("A"));
+        assert_eq!(wheel.drain_due_now(), vec!["B"]);
+        assert_eq!(wheel.now(), 2);
+        assert!(wheel.drain_due_now().is_empty());
+
+        assert_eq!(wheel.tick(1), vec!["C"]);
+        assert!(wheel.drain_due_now().is_empty());
+        assert_eq!(wheel.now(), 3);
+        assert_eq!(capacity(&wheel), before);
+    }
+
+    #[test]
+    fn test_shrink_level() {
+        let mut wheel = HierarchicalTimingWheel::new(2, 16, 10);
+        wheel.preallocate_all_slots(64);
+        wheel.schedule(50, "A").unwrap();
+        wheel.shrink_level(1);
+
+        let capacities =
+            |ring: &Ring<&str>| ring.slots.iter().map(|s| s.capacity()).collect::<Vec<_>>();
+        assert!(capacities(&wheel.rings[0]).iter().all(|&c| c >= 64));
+        assert!(capacities(&wheel.rings[1]).iter().all(|&c| c < 64));
+        assert_eq!(wheel.tick(50), vec!["A"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn test_shrink_level_out_of_range() {
+        HierarchicalTimingWheel::<()>::new(2, 16, 10).shrink_level(2);
+    }
+
+    #[test]
+    fn test_load_deadlines() {
+        let mut wheel = HierarchicalTimingWheel::new(2, 16, 10);
+        wheel.tick(20);
+
+        let rejected = wheel.load_deadlines([(5, "past"), (25, "near"), (20, "now"), (500, "far")]);
+        assert_eq!(rejected, vec![(500, "far")]);
+        assert_eq!(wheel.tick(1), vec!["past", "now"]);
+        assert!(wheel.tick(3).is_empty());
+        assert_eq!(wheel.tick(1), vec!["near"]);
+    }
+
+    #[test]
+    fn test_capacity_of_level() {
+        let mut wheel = HierarchicalTimingWheel::new(2, 4, 10);
+        let before = [wheel.capacity_of_level(0), wheel.capacity_of_level(1)];
+        for n in 0..100 {
+            wheel.schedule(50, n).unwrap();
+        }
+
+        assert_eq!(wheel.capacity_of_level(0), before[0]);
+        assert!(wheel.capacity_of_level(1) >= before[1] + 96);
+        assert_eq!(
+            wheel.total_capacity(),
+            wheel.capacity_of_level(0) + wheel.capacity_of_level(1)
+        );
+        wheel.shrink_level(1);
+        assert!(wheel.capacity_of_level(1) < before[1] + 96);
+    }
+
+    #[test]
+    fn test_const_geometry() {
+        const MAX: usize = max_delay(3, 10);
+        const _: () = assert!(levels_valid(3, 10));
+        assert_eq!(MAX, 999);
+        assert!(!levels_valid(0, 10));
+        assert!(!levels_valid(2, 1));
+
+        let wheel = hierarchical_const::<(), 3, 10>(16);
+        assert_eq!(wheel.max_delay(), MAX);
+        assert_eq!(max_delay(0, 10), 0);
+    }
+
+    #[test]
+    fn test_geometry() {
+        let wheel = HierarchicalTimingWheel::<()>::new(3, 16, 10);
+        let geometry = wheel.geometry();
+        assert_eq!(
+            geometry,
+            Geometry {
+                levels: 3,
+                slots_per_level: 10,
+                slot_capacity: 16,
+                max_delay: 999,
+            }
+        );
+        assert_eq!(geometry.max_delay, wheel.max_delay());
+        assert_eq!(geometry.build::<()>().geometry(), geometry);
+    }
+
+    #[test]
+    fn test_take_due_buffer() {
+        let mut wheel = HierarchicalTimingWheel::new(2, 16, 10);
+        wheel.schedule(1, "A").unwrap();
+        wheel.schedule(3, "B").unwrap();
+        wheel.schedule(12, "C").unwrap();
+
+        wheel.advance(2);
+        wheel.advance(10);
+        assert_eq!(wheel.undelivered(), 3);
+        assert_eq!(wheel.take_due_buffer(), vec!["A", "B", "C"]);
+        assert!(wheel.take_due_buffer().is_empty());
+        assert_eq!(wheel.now(), 12);
+    }
+
+    #[test]
+    fn test_count_by_level() {
+        let mut wheel = HierarchicalTimingWheel::new(3, 16, 10);
+        for delay in [1, 5, 9, 10, 55, 100, 999] {
+            wheel.schedule(delay, delay).unwrap();
+        }
+        assert_eq!(wheel.count_by_level(), vec![3, 2, 2]);
+        wheel.tick(10);
+        assert_eq!(wheel.count_by_level(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_tick_n_returning_first_n() {
+        let mut wheel = HierarchicalTimingWheel::new(2, 16, 10);
+        for (delay, timer) in [(1, "A"), (2, "B"), (2, "C"), (2, "D"), (6, "E")] {
+            wheel.schedule(delay, timer).unwrap();
+        }
+
+        assert_eq!(wheel.tick_n_returning_first_n(10, 2), vec!["A", "B"]);
+        assert_eq!(wheel.now(), 2);
+        assert_eq!(wheel.tick_n_returning_first_n(10, 2), vec!["C", "D"]);
+        assert_eq!(wheel.now(), 2);
+        assert_eq!(wheel.tick_n_returning_first_n(3, 2), Vec::<&str>::new());
+        assert_eq!(wheel.now(), 5);
+        assert_eq!(wheel.tick_n_returning_first_n(10, 2), vec!["E"]);
+        assert_eq!(wheel.now(), 15);
+    }
+
+    #[test]
+    fn test_schedule_chained_does_not_drift() {
+        let mut wheel = HierarchicalTimingWheel::new(3, 16, 10);
+        wheel.schedule_chained(0, 25, 1).unwrap();
+
+        let mut fired_at = Vec::new();
+        while fired_at.len() < 6 {
+            let Some(link) = wheel.tick(1).pop() else {
+                continue;
+            };
+            let fire_tick = wheel.now();
+            fired_at.push(fire_tick);
+            // handling the link takes a few ticks before it schedules the next one
+            wheel.tick(link % 5);
+            wheel.schedule_chained(fire_tick, 25, link + 1).unwrap();
+        }
+        assert_eq!(fired_at, vec![25, 50, 75, 100, 125, 150]);
+    }
+
+    #[test]
+    fn test_slot_filter_is_cleared_when_slot_empties() {
+        let mut wheel = HierarchicalTimingWheel::new(2, 16, 10);
+        wheel.schedule(2, "A").unwrap();
+        assert_ne!(wheel.rings[0].filters[2], 0);
+        wheel.tick(2);
+        assert!(wheel.rings[0].filters.iter().all(|&filter| filter == 0));
+    }
+
+    #[test]
+    fn test_current_slot() {
+        let mut wheel = HierarchicalTimingWheel::<()>::new(2, 16, 10);
+        wheel.tick(23);
+        assert_eq!(wheel.current_slot(0), Some(3));
+        assert_eq!(wheel.current_slot(1), Some(2));
+        assert_eq!(wheel.current_slot(2), None);
+    }
+
+    #[test]
+    fn test_multi_step_tick_order() {
+        let mut wheel = HierarchicalTimingWheel::new(3, 16, 10);
+        for (delay, timer) in [(3, "a"), (20, "b"), (1, "c"), (3, "d"), (20, "e"), (2, "f")] {
+            wheel.schedule(delay, timer).unwrap();
+        }
+        assert_eq!(wheel.tick(15), vec!["c", "f", "a", "d"]);
+
+        // due at tick 20 in level 0, together with b and e graduating from level 1, which were
+        // scheduled first
+        wheel.schedule(5, "g").unwrap();
+        wheel.schedule(5, "h").unwrap();
+        assert_eq!(wheel.tick(5), vec!["b", "e", "g", "h"]);
+    }
+
+    #[test]
+    fn test_tick_partitioned() {
+        let mut wheel = HierarchicalTimingWheel::new(2, 16, 10);
+        for (delay, timer) in [(2, "A"), (5, "B"), (2, "C"), (12, "D")] {
+            wheel.schedule(delay, timer).unwrap();
+        }
+
+        let batches: Vec<_> = wheel.tick_partitioned(15).collect();
+        assert_eq!(
+            batches,
+            vec![(2, vec!["A", "C"]), (5, vec!["B"]), (12, vec!["D"])]
+        );
+    }
+
+    #[test]
+    fn test_for_memory_budget() {
+        let wheel = for_memory_budget::<[u8; 40]>(64 * 1024, 3, 16);
+        assert!(wheel.estimated_memory_bytes() <= 64 * 1024);
+        // 48 slots of 64-byte entries
+        assert_eq!(wheel.slot_capacity(), 21);
+
+        let tiny = for_memory_budget::<u64>(100, 2, 10);
+        assert_eq!(tiny.estimated_memory_bytes(), 0);
+        assert_eq!(tiny.max_delay(), 99);
+    }
+
+    #[test]
+    fn test_tick_single_step_matches_tick() {
+        let mut stepped = HierarchicalTimingWheel::new(3, 16, 10);
+        let mut ticked = HierarchicalTimingWheel::new(3, 16, 10);
+        for wheel in [&mut stepped, &mut ticked] {
+            for delay in [1, 1, 9, 10, 15, 99, 350] {
+                wheel.schedule(delay, delay).unwrap();
+            }
+        }
+        for step in 0..400 {
+            assert_eq!(stepped.tick_single_step(), ticked.tick(1));
+            if step == 200 {
+                // graduated timers land behind newer ones in ring 0
+                for wheel in [&mut stepped, &mut ticked] {
+                    wheel.schedule(5, 5).unwrap();
+                    wheel.schedule(15, 15).unwrap();
+                    wheel.pause();
+                }
+            }
+            if step == 205 {
+                assert_eq!(
+                    stepped.resume(ResumePolicy::CatchUp),
+                    ticked.resume(ResumePolicy::CatchUp)
+                );
+            }
+        }
+        assert_eq!(stepped.now(), ticked.now());
+        assert_eq!(stepped.early_firings(), ticked.early_firings());
+        assert!(stepped.is_empty());
+    }
+
+    #[test]
+    fn test_schedule_aligned() {
+        let mut wheel = HierarchicalTimingWheel::new(3, 16, 10);
+        wheel.tick(3);
+        wheel.schedule_aligned(25, "A").unwrap();
+        wheel.tick(19);
+        wheel.schedule_aligned(25, "B").unwrap();
+        assert!(wheel.tick(2).is_empty());
+        assert_eq!(wheel.tick(1), vec!["A", "B"]);
+        assert_eq!(wheel.now(), 25);
+
+        // on a grid point, the next one is a full period away
+        wheel.schedule_aligned(25, "C").unwrap();
+        assert!(wheel.tick(24).is_empty());
+        assert_eq!(wheel.tick(1), vec!["C"]);
+    }
+
+    #[test]
+    fn test_tick_safe() {
+        let mut wheel = HierarchicalTimingWheel::new(2, 16, 10);
+        for delay in 1..=4 {
+            wheel.schedule(delay, delay).unwrap();
+        }
+
+        let (handled, failed) = wheel.tick_safe(4, |&timer| match timer {
+            2 => Err("refused"),
+            3 => panic!("callback bug"),
+            _ => Ok(()),
+        });
+        assert_eq!(handled, 2);
+        assert!(matches!(failed[0], (2, CallbackError::Failed("refused"))));
+        match &failed[1] {
+            (3, CallbackError::Panicked(payload)) => {
+                assert_eq!(payload.downcast_ref::<&str>(), Some(&"callback bug"))
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+        assert!(wheel.is_empty());
+    }
+
+    #[test]
+    fn test_set_slot_capacity() {
+        let mut wheel = HierarchicalTimingWheel::new(2, 4, 10);
+        for _ in 0..40 {
+            wheel.schedule(3, ()).unwrap();
+        }
+
+        wheel.set_slot_capacity(64);
+        assert_eq!(wheel.slot_capacity(), 64);
+        assert!(wheel.capacity_of_level(1) >= 10 * 64);
+
+        wheel.set_slot_capacity(8);
+        assert_eq!(wheel.geometry().slot_capacity, 8);
+        assert!(wheel.capacity_of_level(1) < 10 * 64);
+        assert!(wheel.capacity_of_level(0) >= 40 + 9 * 8);
+        assert_eq!(wheel.tick(3).len(), 40);
+    }
+
+    #[test]
+    fn test_count_scheduled_for_tick() {
+        let mut wheel = HierarchicalTimingWheel::new(3, 16, 10);
+        for delay in [5, 5, 25, 25, 25, 120, 125] {
+            wheel.schedule(delay, delay).unwrap();
+        }
+        wheel.tick(3);
+
+        assert_eq!(wheel.count_scheduled_for_tick(5), 2);
+        assert_eq!(wheel.count_scheduled_for_tick(25), 3);
+        assert_eq!(wheel.count_scheduled_for_tick(125), 1);
+        assert_eq!(wheel.count_scheduled_for_tick(24), 0);
+        assert_eq!(wheel.count_scheduled_for_tick(3), 0);
+
+        wheel.tick(22);
+        assert_eq!(wheel.count_scheduled_for_tick(120), 1);
+        assert_eq!(wheel.tick_partitioned(100).last(), Some((125, vec![125])));
+    }
+
+    #[test]
+    fn test_slot_load_factor() {
+        let mut wheel = HierarchicalTimingWheel::new(2, 4, 10);
+        assert_eq!(wheel.slot_load_factor(), 0.0);
+        for delay in 1..=40 {
+            wheel.schedule(delay % 20 + 1, delay).unwrap();
+        }
+        assert_eq!(wheel.slot_load_factor(), 0.5);
+        for delay in 1..=60 {
+            wheel.schedule(1, delay).unwrap();
+        }
+        assert!(wheel.slot_load_factor() > 1.0);
+
+        let mut unsized_slots = for_memory_budget(0, 2, 10);
+        assert_eq!(unsized_slots.slot_load_factor(), 0.0);
+        unsized_slots.schedule(1, ()).unwrap();
+        assert_eq!(unsized_slots.slot_load_factor(), f64::INFINITY);
+    }
+
+    #[test]
+    fn test_len_counter() {
+        let mut wheel = WheelBuilder::new(3, 16, 10)
+            .with_lazy_cascade()
+            .with_max_cascade_per_tick(1)
+            .build();
+        let handle = wheel.schedule_with_handle(5, 5).unwrap();
+        wheel.schedule_at_next_slot(1);
+        wheel.schedule_chained(0, 30, 30).unwrap();
+        wheel
+            .schedule_all_or_nothing(vec![(100, 100), (101, 101)])
+            .ok();
+        assert_eq!(wheel.load_deadlines([(7, 7), (u64::MAX, 0)]).len(), 1);
+        assert_eq!(wheel.len(), 6);
+        assert_eq!(wheel.cancel(handle), Some(5));
+        assert_eq!(wheel.drain_filter(|t| *t == 7).count(), 1);
+        assert_eq!(wheel.len(), 4);
+        let fired = wheel.tick(100).len();
+        wheel.check_invariants();
+        assert_eq!(wheel.len(), 4 - fired);
+        wheel.resize_slots(12).unwrap();
+        assert_eq!(wheel.len(), 4 - fired);
+        assert_eq!(wheel.drain_filter(|_| true).count(), 4 - fired);
+        assert!(wheel.is_empty());
+    }
 }
@@ -0,0 +1,64 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// K-way merges per-wheel outputs of `tick_timestamped` into a single stream ordered by fire
+/// tick. Timers sharing a tick keep the order of their input list, earlier lists first.
+pub fn merge_due<T>(outputs: Vec<Vec<(u64, T)>>) -> Vec<T> {
+    let total = outputs.iter().map(Vec::len).sum();
+    let mut streams: Vec<_> = outputs
+        .into_iter()
+        .map(|output| output.into_iter().peekable())
+        .collect();
+    let mut heads = BinaryHeap::with_capacity(streams.len());
+    for (shard, stream) in streams.iter_mut().enumerate() {
+        if let Some((tick, _)) = stream.peek() {
+            heads.push(Reverse((*tick, shard)));
+        }
+    }
+
+    let mut merged = Vec::with_capacity(total);
+    while let Some(Reverse((_, shard))) = heads.pop() {
+        let stream = &mut streams[shard];
+        if let Some((_, timer)) = stream.next() {
+            merged.push(timer);
+        }
+        if let Some((tick, _)) = stream.peek() {
+            heads.push(Reverse((*tick, shard)));
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HierarchicalTimingWheel;
+
+    #[test]
+    fn test_merge_three_shards() {
+        let mut shards: Vec<HierarchicalTimingWheel<&str>> = (0..3)
+            .map(|_| HierarchicalTimingWheel::new(2, 16, 10))
+            .collect();
+        shards[0].schedule(1, "a1").unwrap();
+        shards[0].schedule(4, "a4").unwrap();
+        shards[1].schedule(2, "b2").unwrap();
+        shards[1].schedule(4, "b4").unwrap();
+        shards[1].schedule(15, "b15").unwrap();
+        shards[2].schedule(3, "c3").unwrap();
+        shards[2].schedule(1, "c1").unwrap();
+
+        let outputs = shards
+            .iter_mut()
+            .map(|shard| shard.tick_timestamped(20))
+            .collect();
+        assert_eq!(
+            merge_due(outputs),
+            vec!["a1", "c1", "b2", "c3", "a4", "b4", "b15"]
+        );
+    }
+
+    #[test]
+    fn test_merge_empty() {
+        assert!(merge_due::<()>(vec![vec![], vec![]]).is_empty());
+    }
+}
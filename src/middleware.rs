@@ -0,0 +1,228 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use http::{Request, Response, StatusCode};
+use tokio::sync::oneshot;
+use tower_service::Service;
+
+use crate::{WallClockWheel, hierarchical, max_delay};
+
+const SLOTS_PER_LEVEL: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeoutConfig {
+    pub tick_duration: Duration,
+    pub default_timeout: Duration,
+    /// Requests beyond this many in flight are answered with `503 Service Unavailable`.
+    pub max_concurrent_requests: usize,
+}
+
+struct Shared {
+    wheel: Mutex<WallClockWheel<oneshot::Sender<()>>>,
+    in_flight: AtomicUsize,
+}
+
+/// Tower middleware answering `408 Request Timeout` for requests the inner service does not
+/// complete within `default_timeout`. The wheel is driven by a tokio task spawned by `new`,
+/// which stops once every clone of the middleware is dropped.
+pub struct TimingWheelMiddleware<S> {
+    inner: S,
+    shared: Arc<Shared>,
+    config: TimeoutConfig,
+}
+
+impl<S> TimingWheelMiddleware<S> {
+    /// Must be called from within a tokio runtime.
+    pub fn new(inner: S, config: TimeoutConfig) -> TimingWheelMiddleware<S> {
+        assert!(
+            !config.tick_duration.is_zero(),
+            "tick_duration must be non-zero"
+        );
+        let ticks = config.default_timeout.as_nanos() / config.tick_duration.as_nanos() + 1;
+        let levels = (1..)
+            .find(|&levels| max_delay(levels, SLOTS_PER_LEVEL) as u128 >= ticks)
+            .unwrap();
+        let wheel = hierarchical(levels, 4, SLOTS_PER_LEVEL);
+        let shared = Arc::new(Shared {
+            wheel: Mutex::new(WallClockWheel::new(wheel, config.tick_duration)),
+            in_flight: AtomicUsize::new(0),
+        });
+        tokio::spawn(drive(Arc::downgrade(&shared), config.tick_duration));
+        Self {
+            inner,
+            shared,
+            config,
+        }
+    }
+}
+
+async fn drive(shared: Weak<Shared>, tick_duration: Duration) {
+    let mut interval = tokio::time::interval(tick_duration);
+    loop {
+        interval.tick().await;
+        let Some(shared) = shared.upgrade() else {
+            return;
+        };
+        let fired = shared.wheel.lock().unwrap().poll_now();
+        for timeout in fired {
+            // the request may have completed already, dropping its receiver
+            let _ = timeout.send(());
+        }
+    }
+}
+
+impl<S: Clone> Clone for TimingWheelMiddleware<S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            shared: Arc::clone(&self.shared),
+            config: self.config,
+        }
+    }
+}
+
+// Releases a request's concurrency slot however its future ends.
+struct InFlight(Arc<Shared>);
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.in_flight.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+fn respond<B: Default>(status: StatusCode) -> Response<B> {
+    let mut response = Response::new(B::default());
+    *response.status_mut() = status;
+    response
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for TimingWheelMiddleware<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    S::Future: Send + 'static,
+    ResBody: Default + Send + 'static,
+{
+    type Response = Response<ResBody>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<ReqBody>) -> Self::Future {
+        let previous = self.shared.in_flight.fetch_add(1, Ordering::Relaxed);
+        let in_flight = InFlight(Arc::clone(&self.shared));
+        if previous >= self.config.max_concurrent_requests {
+            return Box::pin(async { Ok(respond(StatusCode::SERVICE_UNAVAILABLE)) });
+        }
+
+        let (tx, rx) = oneshot::channel();
+        let deadline = Instant::now() + self.config.default_timeout;
+        let scheduled = self
+            .shared
+            .wheel
+            .lock()
+            .unwrap()
+            .schedule_deadline_exact(deadline, tx);
+        let response = self.inner.call(request);
+        let shared = Arc::clone(&self.shared);
+        Box::pin(async move {
+            let _in_flight = in_flight;
+            let Ok(handle) = scheduled else {
+                return response.await;
+            };
+            tokio::select! {
+                result = response => {
+                    // drop the sender now rather than leave it in the wheel until the deadline
+                    shared.wheel.lock().unwrap().wheel_mut().cancel(handle);
+                    result
+                }
+                _ = rx => Ok(respond(StatusCode::REQUEST_TIMEOUT)),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Takes as many milliseconds to answer as the request body says.
+    #[derive(Clone)]
+    struct Sleepy;
+
+    impl Service<Request<u64>> for Sleepy {
+        type Response = Response<String>;
+        type Error = ();
+        type Future = Pin<Box<dyn Future<Output = Result<Response<String>, ()>> + Send>>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), ()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, request: Request<u64>) -> Self::Future {
+            let millis = *request.body();
+            Box::pin(async move {
+                tokio::time::sleep(Duration::from_millis(millis)).await;
+                Ok(Response::new("done".to_string()))
+            })
+        }
+    }
+
+    fn config(max_concurrent_requests: usize) -> TimeoutConfig {
+        TimeoutConfig {
+            tick_duration: Duration::from_millis(5),
+            default_timeout: Duration::from_millis(50),
+            max_concurrent_requests,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_request_timeout() {
+        let mut service = TimingWheelMiddleware::new(Sleepy, config(10));
+
+        let fast = service.call(Request::new(1)).await.unwrap();
+        assert_eq!(fast.status(), StatusCode::OK);
+        assert_eq!(fast.body(), "done");
+        let slow = service.call(Request::new(500)).await.unwrap();
+        assert_eq!(slow.status(), StatusCode::REQUEST_TIMEOUT);
+    }
+
+    #[tokio::test]
+    async fn test_timeout_beyond_one_rotation() {
+        // 100 ticks reach past ring 0's 64 slots
+        let mut service = TimingWheelMiddleware::new(
+            Sleepy,
+            TimeoutConfig {
+                tick_duration: Duration::from_millis(1),
+                default_timeout: Duration::from_millis(100),
+                max_concurrent_requests: 10,
+            },
+        );
+        // 40 ticks into the rotation, a timeout aligned to ring 1 would fire after 60 ms
+        tokio::time::sleep(Duration::from_millis(40)).await;
+
+        let response = service.call(Request::new(70)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(service.shared.wheel.lock().unwrap().wheel().is_empty());
+        let slow = service.call(Request::new(500)).await.unwrap();
+        assert_eq!(slow.status(), StatusCode::REQUEST_TIMEOUT);
+    }
+
+    #[tokio::test]
+    async fn test_max_concurrent_requests() {
+        let mut service = TimingWheelMiddleware::new(Sleepy, config(1));
+
+        let first = service.call(Request::new(10));
+        let second = service.call(Request::new(10)).await.unwrap();
+        assert_eq!(second.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(first.await.unwrap().status(), StatusCode::OK);
+        let third = service.call(Request::new(1)).await.unwrap();
+        assert_eq!(third.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,115 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::{HierarchicalTimingWheel, ScheduleError};
+
+type SeqNo = u64;
+
+/// Wheel that can also hand out its most urgent timer in `O(log n)`.
+///
+/// Payloads live in a map keyed by insertion order while the wheel and a heap of
+/// `(fire_tick, SeqNo)` both refer to them by that key. Popping removes the payload only; the
+/// leftover wheel entry is skipped when it fires.
+pub struct MinHeapWheel<T> {
+    wheel: HierarchicalTimingWheel<SeqNo>,
+    timers: HashMap<SeqNo, T>,
+    heap: BinaryHeap<Reverse<(u64, SeqNo)>>,
+    next_seq: SeqNo,
+}
+
+impl<T> MinHeapWheel<T> {
+    pub fn new(levels: u32, slot_capacity: usize, slots_per_level: usize) -> MinHeapWheel<T> {
+        Self {
+            wheel: HierarchicalTimingWheel::new(levels, slot_capacity, slots_per_level),
+            timers: HashMap::new(),
+            heap: BinaryHeap::new(),
+            next_seq: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.timers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.timers.is_empty()
+    }
+
+    pub fn schedule(
+        &mut self,
+        delay_ticks: usize,
+        timer: T,
+    ) -> Result<(usize, usize), ScheduleError> {
+        let seq = self.next_seq;
+        let (level, slot) = self.wheel.schedule(delay_ticks, seq)?;
+        let fire_tick = self.wheel.now() + self.wheel.last_due_in(level, slot) as u64;
+        self.next_seq += 1;
+        self.timers.insert(seq, timer);
+        self.heap.push(Reverse((fire_tick, seq)));
+        Ok((level, slot))
+    }
+
+    /// Removes the timer closest to firing, breaking ties by insertion order, and returns it
+    /// with the number of ticks it had left.
+    pub fn pop_min(&mut self) -> Option<(usize, T)> {
+        while let Some(Reverse((fire_tick, seq))) = self.heap.pop() {
+            if let Some(timer) = self.timers.remove(&seq) {
+                return Some(((fire_tick - self.wheel.now()) as usize, timer));
+            }
+        }
+        None
+    }
+
+    pub fn tick(&mut self, steps: usize) -> Vec<T> {
+        let due = self
+            .wheel
+            .tick(steps)
+            .into_iter()
+            .filter_map(|seq| self.timers.remove(&seq))
+            .collect();
+        let now = self.wheel.now();
+        while self
+            .heap
+            .peek()
+            .is_some_and(|Reverse((fire_tick, _))| *fire_tick <= now)
+        {
+            self.heap.pop();
+        }
+        due
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pop_min() {
+        let mut wheel = MinHeapWheel::new(3, 16, 10);
+        wheel.schedule(150, "A").unwrap();
+        wheel.schedule(7, "B").unwrap();
+        wheel.schedule(12, "C").unwrap();
+        wheel.tick(5);
+        wheel.schedule(2, "D").unwrap();
+
+        assert_eq!(wheel.pop_min(), Some((2, "B")));
+        assert_eq!(wheel.pop_min(), Some((2, "D")));
+        assert_eq!(wheel.len(), 2);
+        assert_eq!(wheel.tick(10), vec!["C"]);
+        assert_eq!(wheel.pop_min(), Some((135, "A")));
+        assert_eq!(wheel.pop_min(), None);
+        assert!(wheel.tick(200).is_empty());
+        assert!(wheel.is_empty());
+    }
+
+    #[test]
+    fn test_heap_drops_fired_timers() {
+        let mut wheel = MinHeapWheel::new(2, 16, 10);
+        for delay in 1..50 {
+            wheel.schedule(delay, delay).unwrap();
+        }
+        assert_eq!(wheel.tick(30).len(), 30);
+        assert_eq!(wheel.heap.len(), 19);
+        assert_eq!(wheel.pop_min(), Some((1, 31)));
+    }
+}
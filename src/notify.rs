@@ -0,0 +1,65 @@
+use crate::{HierarchicalTimingWheel, ScheduleError, TimerHandle};
+
+/// Wheel that delivers each fired timer to the `oneshot::Receiver` handed out when it was
+/// scheduled, rather than returning it from `tick`.
+pub struct NotifyingWheel<T> {
+    wheel: HierarchicalTimingWheel<(oneshot::Sender<T>, T)>,
+}
+
+impl<T> NotifyingWheel<T> {
+    pub fn new(levels: u32, slot_capacity: usize, slots_per_level: usize) -> NotifyingWheel<T> {
+        Self {
+            wheel: HierarchicalTimingWheel::new(levels, slot_capacity, slots_per_level),
+        }
+    }
+
+    pub fn schedule_notified(
+        &mut self,
+        delay_ticks: usize,
+        timer: T,
+    ) -> Result<(TimerHandle, oneshot::Receiver<T>), ScheduleError> {
+        let (tx, rx) = oneshot::channel();
+        let handle = self.wheel.schedule_with_handle(delay_ticks, (tx, timer))?;
+        Ok((handle, rx))
+    }
+
+    /// Cancelled timers are returned here and never reach their receiver.
+    pub fn cancel(&mut self, handle: TimerHandle) -> Option<T> {
+        self.wheel.cancel(handle).map(|(_, timer)| timer)
+    }
+
+    /// Ticks `steps` times and sends every fired timer to its receiver. Returns the timers whose
+    /// receiver was already dropped.
+    pub fn tick(&mut self, steps: usize) -> Vec<T> {
+        self.wheel
+            .tick(steps)
+            .into_iter()
+            .filter_map(|(tx, timer)| tx.send(timer).err().map(|err| err.into_inner()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schedule_notified() {
+        let mut wheel = NotifyingWheel::new(2, 16, 10);
+        let (_, a) = wheel.schedule_notified(5, "A").unwrap();
+        let (_, b) = wheel.schedule_notified(15, "B").unwrap();
+        let (c_handle, c) = wheel.schedule_notified(15, "C").unwrap();
+        let (_, d) = wheel.schedule_notified(20, "D").unwrap();
+        drop(d);
+
+        assert!(wheel.tick(4).is_empty());
+        assert!(a.try_recv().is_err());
+        assert!(wheel.tick(1).is_empty());
+        assert_eq!(a.recv(), Ok("A"));
+
+        assert_eq!(wheel.cancel(c_handle), Some("C"));
+        assert_eq!(wheel.tick(20), vec!["D"]);
+        assert_eq!(b.recv(), Ok("B"));
+        assert!(c.recv().is_err());
+    }
+}
@@ -0,0 +1,32 @@
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+use crate::HierarchicalTimingWheel;
+
+impl<T: Send> HierarchicalTimingWheel<T> {
+    /// Consumes the wheel, ticks it `steps` times and returns the fired timers as a rayon
+    /// parallel iterator. The tick itself is sequential; only processing the fired batch runs on
+    /// the thread pool. Timers still pending afterwards are dropped with the wheel.
+    pub fn into_par_iter_fired(mut self, steps: usize) -> impl ParallelIterator<Item = T> {
+        self.tick(steps).into_par_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_into_par_iter_fired() {
+        let mut wheel = HierarchicalTimingWheel::new(2, 16, 10);
+        for n in 1..=50u64 {
+            wheel.schedule(n as usize, n).unwrap();
+        }
+
+        let (count, sum) = wheel
+            .into_par_iter_fired(20)
+            .map(|n| (1, n))
+            .reduce(|| (0, 0), |(c1, s1), (c2, s2)| (c1 + c2, s1 + s2));
+        assert_eq!(count, 20);
+        assert_eq!(sum, (1..=20).sum());
+    }
+}
@@ -0,0 +1,77 @@
+use crate::HierarchicalTimingWheel;
+
+/// What `resume` does with the ticks that arrived while the wheel was paused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResumePolicy {
+    /// Applies every paused tick at once, firing whatever came due during the pause.
+    CatchUp,
+    /// Drops the paused ticks, so the wheel's clock stood still and every pending timer,
+    /// including those scheduled while paused, is due as many ticks later as it was at pause.
+    Skip,
+}
+
+impl<T> HierarchicalTimingWheel<T> {
+    /// Stops the wheel from advancing. Ticks are counted instead of applied, so nothing fires,
+    /// but timers can still be scheduled and cancelled. Timers already in the due buffer are
+    /// still delivered. Pausing a paused wheel does nothing.
+    pub fn pause(&mut self) {
+        self.paused_ticks.get_or_insert(0);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_ticks.is_some()
+    }
+
+    /// Ticks received since `pause`, or `None` if the wheel is running.
+    pub fn paused_ticks(&self) -> Option<usize> {
+        self.paused_ticks
+    }
+
+    /// Restarts the wheel, applying or dropping the paused ticks per `policy`, and returns the
+    /// timers that fired while catching up. Resuming a running wheel returns nothing.
+    pub fn resume(&mut self, policy: ResumePolicy) -> Vec<T> {
+        match (self.paused_ticks.take(), policy) {
+            (Some(steps), ResumePolicy::CatchUp) => self.tick(steps),
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paused_wheel() -> HierarchicalTimingWheel<&'static str> {
+        let mut wheel = HierarchicalTimingWheel::new(2, 16, 10);
+        wheel.schedule(3, "A").unwrap();
+        wheel.tick(1);
+        wheel.pause();
+        wheel.schedule(2, "B").unwrap();
+        wheel.schedule(8, "C").unwrap();
+        assert!(wheel.tick(5).is_empty());
+        assert_eq!(wheel.paused_ticks(), Some(5));
+        assert_eq!(wheel.now(), 1);
+        wheel
+    }
+
+    #[test]
+    fn test_resume_catches_up() {
+        let mut wheel = paused_wheel();
+        assert_eq!(wheel.resume(ResumePolicy::CatchUp), vec!["A", "B"]);
+        assert!(!wheel.is_paused());
+        assert_eq!(wheel.now(), 6);
+        assert!(wheel.tick(2).is_empty());
+        assert_eq!(wheel.tick(1), vec!["C"]);
+    }
+
+    #[test]
+    fn test_resume_skips() {
+        let mut wheel = paused_wheel();
+        assert!(wheel.resume(ResumePolicy::Skip).is_empty());
+        assert_eq!(wheel.now(), 1);
+        assert_eq!(wheel.tick(2), vec!["A", "B"]);
+        assert!(wheel.tick(5).is_empty());
+        assert_eq!(wheel.tick(1), vec!["C"]);
+        assert!(wheel.resume(ResumePolicy::CatchUp).is_empty());
+    }
+}
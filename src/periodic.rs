@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+
+use rand::{Rng, RngExt};
+
+use crate::{HierarchicalTimingWheel, ScheduleError, TimerHandle};
+
+struct Periodic<T> {
+    series: u64,
+    period: usize,
+    jitter: usize,
+    timer: T,
+}
+
+/// Wheel of recurring timers that re-fire every `period` ticks plus a fresh random jitter, so
+/// timers started together spread out instead of firing in bursts.
+pub struct JitteredWheel<T> {
+    wheel: HierarchicalTimingWheel<Periodic<T>>,
+    // handle of the pending firing of each series
+    handles: HashMap<u64, TimerHandle>,
+    next_series: u64,
+}
+
+impl<T: Clone> JitteredWheel<T> {
+    pub fn new(levels: u32, slot_capacity: usize, slots_per_level: usize) -> JitteredWheel<T> {
+        Self {
+            wheel: HierarchicalTimingWheel::new(levels, slot_capacity, slots_per_level),
+            handles: HashMap::new(),
+            next_series: 0,
+        }
+    }
+
+    /// Schedules `timer` to fire after `period` plus a jitter drawn from `0..jitter` ticks, and
+    /// again after each firing with a new jitter. The handle is only good for the first firing;
+    /// `tick` reports the handle of each later one. Fails if the longest jittered period does not
+    /// fit the wheel.
+    pub fn schedule_periodic_jittered<R: Rng>(
+        &mut self,
+        period: usize,
+        jitter: usize,
+        timer: T,
+        rng: &mut R,
+    ) -> Result<TimerHandle, ScheduleError> {
+        if period.saturating_add(jitter.saturating_sub(1)) > self.wheel.max_delay() {
+            return Err(ScheduleError::DelayTooLarge);
+        }
+        let series = self.next_series;
+        let periodic = Periodic {
+            series,
+            period,
+            jitter,
+            timer,
+        };
+        let handle = self
+            .wheel
+            .schedule_with_handle(jittered(period, jitter, rng), periodic)?;
+        self.next_series += 1;
+        self.handles.insert(series, handle);
+        Ok(handle)
+    }
+
+    /// Stops a recurring timer, given the handle of its pending firing.
+    pub fn cancel(&mut self, handle: TimerHandle) -> Option<T> {
+        let periodic = self.wheel.cancel(handle)?;
+        self.handles.remove(&periodic.series);
+        Some(periodic.timer)
+    }
+
+    /// Advances `steps` ticks and returns a copy of every timer that fired, rescheduling each
+    /// one with a new jitter and calling `on_rescheduled(old, new)` with its handles.
+    pub fn tick<R: Rng>(
+        &mut self,
+        steps: usize,
+        rng: &mut R,
+        mut on_rescheduled: impl FnMut(TimerHandle, TimerHandle),
+    ) -> Vec<T> {
+        let mut due = Vec::new();
+        for _ in 0..steps {
+            for periodic in self.wheel.tick(1) {
+                due.push(periodic.timer.clone());
+                let series = periodic.series;
+                let delay = jittered(periodic.period, periodic.jitter, rng);
+                let handle = self
+                    .wheel
+                    .schedule_with_handle(delay, periodic)
+                    .expect("jittered period was checked to fit");
+                if let Some(old) = self.handles.insert(series, handle) {
+                    on_rescheduled(old, handle);
+                }
+            }
+        }
+        due
+    }
+}
+
+fn jittered<R: Rng>(period: usize, jitter: usize, rng: &mut R) -> usize {
+    if jitter == 0 {
+        period
+    } else {
+        period + rng.random_range(0..jitter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    use super::*;
+
+    #[test]
+    fn test_refires_within_jitter_window() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut wheel = JitteredWheel::new(2, 16, 10);
+        let mut handle = wheel
+            .schedule_periodic_jittered(4, 3, "beat", &mut rng)
+            .unwrap();
+
+        let mut last_fire = 0;
+        for tick in 1..=60 {
+            let mut moved = None;
+            let due = wheel.tick(1, &mut rng, |old, new| moved = Some((old, new)));
+            if due.is_empty() {
+                assert!(moved.is_none());
+                continue;
+            }
+            assert_eq!(due, vec!["beat"]);
+            assert!((4..7).contains(&(tick - last_fire)));
+            last_fire = tick;
+            let (old, new) = moved.unwrap();
+            assert_eq!(old, handle);
+            handle = new;
+        }
+        assert!(last_fire > 60 - 7);
+
+        assert_eq!(wheel.cancel(handle), Some("beat"));
+        assert!(wheel.tick(20, &mut rng, |_, _| panic!()).is_empty());
+    }
+
+    #[test]
+    fn test_rejects_period_beyond_max_delay() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut wheel = JitteredWheel::new(1, 16, 10);
+        assert!(wheel.schedule_periodic_jittered(8, 2, (), &mut rng).is_ok());
+        assert!(matches!(
+            wheel.schedule_periodic_jittered(8, 3, (), &mut rng),
+            Err(ScheduleError::DelayTooLarge)
+        ));
+    }
+}
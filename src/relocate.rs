@@ -0,0 +1,316 @@
+use crate::{Entry, HierarchicalTimingWheel, Ring, ScheduleError};
+
+impl<T> HierarchicalTimingWheel<T> {
+    /// Takes every pending timer out of the wheel and re-places it so that it still fires at the
+    /// same tick under the wheel's current geometry.
+    pub fn relocate_all(&mut self) {
+        for (due_in, entry) in self.drain_pending() {
+            self.place_exact(due_in, entry)
+                .ok()
+                .expect("a pending timer always fits the geometry it came from");
+        }
+    }
+
+    /// Rebuilds the rings with `new_slots_per_level` slots each, keeping the number of levels,
+    /// and re-places every pending timer so it still fires at the same tick. Fails with
+    /// `DelayTooLarge`, leaving the wheel as it was, if a timer would not fit the new geometry.
+    /// Panics if `new_slots_per_level` is below 2.
+    pub fn resize_slots(&mut self, new_slots_per_level: usize) -> Result<(), ScheduleError> {
+        assert!(new_slots_per_level >= 2, "a ring needs at least 2 slots");
+        let rings: Vec<Ring<T>> = (0..self.rings.len() as u32)
+            .map(|level| {
+                let mut ring = Ring::new(level, self.slot_capacity, new_slots_per_level);
+                ring.cursor =
+                    (self.current_tick / ring.span() as u64 % new_slots_per_level as u64) as usize;
+                ring
+            })
+            .collect();
+        // same test as `exact_level`, against the new rings' cursors
+        let fits = |due_in: usize| {
+            let mut position = 0;
+            rings.iter().any(|ring| {
+                let aligned = due_in.max(1) + position;
+                position += ring.cursor * ring.span();
+                aligned < ring.capacity() && aligned >= ring.span()
+            })
+        };
+        if !self.located().all(|located| fits(located.due_in)) {
+            return Err(ScheduleError::DelayTooLarge);
+        }
+
+        let pending = self.drain_pending();
+        self.rings = rings;
+        self.slots_per_level = new_slots_per_level;
+        for (due_in, entry) in pending {
+            self.place_exact(due_in, entry)
+                .ok()
+                .expect("every timer was checked to fit the new geometry");
+        }
+        Ok(())
+    }
+
+    /// Moves every timer that sits in a coarser ring than its remaining delay needs, or whose
+    /// stored remainder no longer fits its ring, to where it belongs. Fire ticks are kept.
+    /// Returns the number of timers moved.
+    pub fn rebalance(&mut self) -> usize {
+        let mut misplaced: Vec<(usize, usize, usize, usize)> = self
+            .located()
+            .filter(|located| {
+                located.entry.remaining >= self.rings[located.level].span()
+                    || self.exact_level(located.due_in) != Some(located.level)
+            })
+            .map(|located| (located.level, located.slot, located.index, located.due_in))
+            .collect();
+        misplaced.sort_unstable_by(|a, b| b.cmp(a));
+
+        let mut moved: Vec<(usize, Entry<T>)> = misplaced
+            .into_iter()
+            .filter_map(|(level, slot, index, due_in)| {
+                let entry = self.rings[level].slots[slot].remove(index)?;
+                Some((due_in, entry))
+            })
+            .collect();
+        moved.sort_by_key(|(due_in, entry)| (*due_in, entry.id));
+        let count = moved.len();
+        for (due_in, entry) in moved {
+            self.place_exact(due_in, entry)
+                .ok()
+                .expect("a pending timer always fits the geometry it came from");
+        }
+        count
+    }
+
+    /// Empties the ring at `level` as if all of its slots had come due now, without advancing
+    /// the clock. Timers with no remainder left, and every timer of level 0, are returned as
+    /// due. The others fire their remainder from now and move to the ring that fits it.
+    pub fn flush_level(&mut self, level: usize) -> Vec<T> {
+        let Some(ring) = self.rings.get_mut(level) else {
+            return Vec::new();
+        };
+        let slots = ring.slots.len();
+        let mut flushed = Vec::new();
+        for offset in 1..=slots {
+            let slot = (ring.cursor + offset) % slots;
+            flushed.extend(ring.drain_slot(slot));
+        }
+
+        let mut due = Vec::new();
+        for entry in flushed {
+            if level == 0 || entry.remaining == 0 {
+                let timer = self.fire_entry(entry);
+                due.push(timer);
+            } else {
+                self.place_exact(entry.remaining, entry)
+                    .ok()
+                    .expect("a remainder always fits below its ring");
+            }
+        }
+        due
+    }
+
+    // Removes every pending timer, ordered by the number of ticks until it fires and then by
+    // scheduling order.
+    pub(crate) fn drain_pending(&mut self) -> Vec<(usize, Entry<T>)> {
+        let mut pending = Vec::new();
+        for level in 0..self.rings.len() {
+            let position = self.position(level);
+            for slot in 0..self.rings[level].slots.len() {
+                let base = self.offset(level, slot) * self.rings[level].span();
+                pending.extend(
+                    self.rings[level].slots[slot]
+                        .drain(..)
+                        .map(|entry| (base + entry.remaining - position, entry)),
+                );
+            }
+        }
+        pending.sort_by_key(|(due_in, entry)| (*due_in, entry.id));
+        pending
+    }
+
+    // Places a timer so it fires exactly `delay_ticks` from now. Unlike `place`, which aligns
+    // upper-level timers to the ring's rotation, this accounts for the ticks the lower rings have
+    // already advanced.
+    pub(crate) fn place_exact(
+        &mut self,
+        delay_ticks: usize,
+        entry: Entry<T>,
+    ) -> Result<(usize, usize), Entry<T>> {
+        let delay_ticks = delay_ticks.max(1);
+        let Some(level) = self.exact_level(delay_ticks) else {
+            return Err(entry);
+        };
+        let aligned = delay_ticks + self.position(level);
+        let slot = self.rings[level].place(aligned, entry);
+        Ok((level, slot))
+    }
+
+    pub(crate) fn exact_level(&self, delay_ticks: usize) -> Option<usize> {
+        (0..self.rings.len()).find(|&level| {
+            let ring = &self.rings[level];
+            let aligned = delay_ticks + self.position(level);
+            aligned < ring.capacity() && aligned >= ring.span()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use crate::{HierarchicalTimingWheel, ScheduleError};
+
+    fn wheel() -> HierarchicalTimingWheel<usize> {
+        let mut wheel = HierarchicalTimingWheel::new(3, 16, 10);
+        wheel.tick(37);
+        for delay in [1, 5, 10, 15, 63, 64, 99, 250, 900] {
+            wheel.schedule(delay, delay).unwrap();
+        }
+        wheel
+    }
+
+    #[test]
+    fn test_relocate_all_keeps_fire_ticks() {
+        let mut relocated = wheel();
+        relocated.relocate_all();
+        assert_eq!(relocated.len(), 9);
+
+        let mut expected = wheel().tick_timestamped(1000);
+        let mut fired = relocated.tick_timestamped(1000);
+        expected.sort();
+        fired.sort();
+        assert_eq!(fired, expected);
+    }
+
+    #[test]
+    fn test_relocate_all_across_geometries() {
+        let mut relocated = wheel();
+        relocated.tick(25);
+        assert_eq!(relocated.current_slot(0), Some(2));
+        assert_eq!(relocated.current_slot(1), Some(6));
+        let pending = relocated.len();
+        for slots_per_level in [12, 11] {
+            relocated.resize_slots(slots_per_level).unwrap();
+            relocated.relocate_all();
+            relocated.check_invariants();
+            assert_eq!(relocated.len(), pending);
+        }
+
+        let mut reference = wheel();
+        reference.tick(25);
+        let mut expected = reference.tick_timestamped(1000);
+        let mut fired = relocated.tick_timestamped(1000);
+        expected.sort();
+        fired.sort();
+        assert_eq!(fired, expected);
+    }
+
+    #[test]
+    fn test_place_exact() {
+        let mut wheel = HierarchicalTimingWheel::new(3, 16, 10);
+        wheel.tick(37);
+        let a = wheel.new_entry(10, "A");
+        assert_eq!(wheel.place_exact(10, a).ok(), Some((1, 4)));
+        wheel.issue_id();
+        let b = wheel.new_entry(3, "B");
+        assert_eq!(wheel.place_exact(3, b).ok(), Some((0, 0)));
+        wheel.issue_id();
+        assert_eq!(wheel.next_expiry(), Some(3));
+        assert_eq!(wheel.tick(9), vec!["B"]);
+        assert_eq!(wheel.tick(1), vec!["A"]);
+    }
+
+    #[test]
+    fn test_rebalance() {
+        let mut wheel = HierarchicalTimingWheel::new(3, 16, 10);
+        wheel.tick(37);
+        // 10 and 15 land at level 1 but only have 3 and 8 ticks left, 99 has 92 left at level 1
+        // which is where it belongs
+        for delay in [5, 10, 15, 99] {
+            wheel.schedule(delay, delay).unwrap();
+        }
+        assert_eq!(wheel.peek_at_slot(1, 4), Some(vec![&10, &15]));
+
+        assert_eq!(wheel.rebalance(), 2);
+        assert_eq!(wheel.peek_at_slot(1, 4), Some(vec![]));
+        assert_eq!(wheel.peek_at_slot(0, 0), Some(vec![&10]));
+        assert_eq!(wheel.peek_at_slot(0, 5), Some(vec![&15]));
+        assert_eq!(wheel.rebalance(), 0);
+
+        let fired = wheel.tick_timestamped(200);
+        assert_eq!(fired, vec![(40, 10), (42, 5), (45, 15), (129, 99)]);
+    }
+
+    #[test]
+    fn test_flush_level() {
+        let mut wheel = HierarchicalTimingWheel::new(3, 16, 10);
+        wheel.schedule(250, "A").unwrap();
+        wheel.schedule(123, "B").unwrap();
+        wheel.schedule(200, "C").unwrap();
+        wheel.schedule(45, "D").unwrap();
+        wheel.schedule(3, "E").unwrap();
+        let capacity = |wheel: &HierarchicalTimingWheel<_>| -> usize {
+            wheel.rings[2].slots.iter().map(VecDeque::capacity).sum()
+        };
+        let before = capacity(&wheel);
+
+        assert_eq!(wheel.flush_level(2), vec!["C"]);
+        assert_eq!(capacity(&wheel), before);
+        assert_eq!(wheel.peek_entries_at_slot(1, 2), Some(vec![(3, &"B")]));
+        assert_eq!(wheel.peek_entries_at_slot(1, 5), Some(vec![(0, &"A")]));
+        assert_eq!(wheel.peek_entries_at_slot(1, 4), Some(vec![(5, &"D")]));
+        assert_eq!(wheel.now(), 0);
+
+        assert_eq!(wheel.flush_level(0), vec!["E"]);
+        assert!(wheel.flush_level(3).is_empty());
+        assert_eq!(
+            wheel.tick_timestamped(100),
+            vec![(23, "B"), (45, "D"), (50, "A")]
+        );
+    }
+
+    #[test]
+    fn test_resize_slots() {
+        let mut resized = wheel();
+        let mut reference = wheel();
+        resized.tick(5);
+        reference.tick(5);
+
+        resized.resize_slots(16).unwrap();
+        resized.check_invariants();
+        assert_eq!(resized.geometry().slots_per_level, 16);
+        resized.tick(60);
+        reference.tick(60);
+        assert!(matches!(
+            resized.resize_slots(9),
+            Err(ScheduleError::DelayTooLarge)
+        ));
+        resized.resize_slots(11).unwrap();
+        resized.check_invariants();
+
+        let resized: Vec<_> = resized.tick_partitioned(1000).collect();
+        let reference: Vec<_> = reference.tick_partitioned(1000).collect();
+        assert_eq!(resized, reference);
+    }
+
+    #[test]
+    fn test_resize_slots_with_lower_cursors() {
+        let mut wheel = crate::hierarchical(2, 4, 20);
+        wheel.tick(5);
+        wheel.schedule_chained(5, 99, "A").unwrap();
+        // 99 is below the new max delay, but ring 0 is 5 ticks into its rotation
+        assert!(matches!(
+            wheel.resize_slots(10),
+            Err(ScheduleError::DelayTooLarge)
+        ));
+        wheel.check_invariants();
+        assert_eq!(wheel.geometry().slots_per_level, 20);
+        assert_eq!(wheel.tick_timestamped(200), vec![(104, "A")]);
+
+        let mut wheel = crate::hierarchical(2, 4, 20);
+        wheel.tick(5);
+        wheel.schedule_chained(5, 90, "B").unwrap();
+        wheel.resize_slots(10).unwrap();
+        wheel.check_invariants();
+        assert_eq!(wheel.tick_timestamped(200), vec![(95, "B")]);
+    }
+}
@@ -0,0 +1,36 @@
+use smallvec::SmallVec;
+
+use crate::{HierarchicalTimingWheel, TickDiagnostics};
+
+impl<T> HierarchicalTimingWheel<T> {
+    /// Like `tick`, but keeps up to `N` fired timers inline instead of allocating.
+    pub fn tick_small<const N: usize>(&mut self, steps: usize) -> SmallVec<[T; N]> {
+        let mut due: SmallVec<[T; N]> = self.undelivered.drain(..).collect();
+        let mut diagnostics = TickDiagnostics::default();
+        for _ in 0..steps {
+            self.step(&mut diagnostics, |timer| due.push(timer));
+        }
+        due
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tick_small() {
+        let mut wheel = HierarchicalTimingWheel::new(2, 16, 10);
+        wheel.schedule(1, "A").unwrap();
+        for _ in 0..3 {
+            wheel.schedule(15, "B").unwrap();
+        }
+
+        let due = wheel.tick_small::<2>(1);
+        assert_eq!(due.as_slice(), ["A"]);
+        assert!(!due.spilled());
+        let due = wheel.tick_small::<2>(14);
+        assert_eq!(due.as_slice(), ["B", "B", "B"]);
+        assert!(due.spilled());
+    }
+}
@@ -0,0 +1,105 @@
+use crate::HierarchicalTimingWheel;
+
+/// Pull-based driver returned by `steps`: every `next` advances the wheel by one tick and yields
+/// the timers due at that tick. It never ends on its own, stop by dropping it or with `take`.
+pub struct StepIterator<'a, T> {
+    wheel: &'a mut HierarchicalTimingWheel<T>,
+}
+
+/// Consuming iterator returned by `into_iter_sorted`.
+pub struct IntoIterSorted<T> {
+    wheel: HierarchicalTimingWheel<T>,
+    due: std::vec::IntoIter<T>,
+    pending: usize,
+}
+
+impl<T> HierarchicalTimingWheel<T> {
+    pub fn steps(&mut self) -> StepIterator<'_, T> {
+        StepIterator { wheel: self }
+    }
+
+    /// Consumes the wheel, yielding the due buffer and then every pending timer in the order
+    /// `tick` would fire them. The wheel is ticked only as far as the caller iterates, and a
+    /// paused wheel is resumed first.
+    pub fn into_iter_sorted(mut self) -> IntoIterSorted<T> {
+        self.paused_ticks = None;
+        IntoIterSorted {
+            due: self.take_due_buffer().into_iter(),
+            pending: self.len(),
+            wheel: self,
+        }
+    }
+}
+
+impl<T> Iterator for StepIterator<'_, T> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Vec<T>> {
+        Some(self.wheel.tick(1))
+    }
+}
+
+impl<T> Iterator for IntoIterSorted<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        loop {
+            if let Some(timer) = self.due.next() {
+                return Some(timer);
+            }
+            if self.pending == 0 {
+                return None;
+            }
+            let due = self.wheel.tick_single_step();
+            self.pending -= due.len();
+            self.due = due.into_iter();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::HierarchicalTimingWheel;
+
+    fn wheel() -> HierarchicalTimingWheel<usize> {
+        let mut wheel = HierarchicalTimingWheel::new(3, 16, 10);
+        for delay in [3, 3, 17, 40, 41, 120] {
+            wheel.schedule(delay, delay).unwrap();
+        }
+        wheel
+    }
+
+    #[test]
+    fn test_steps_match_tick() {
+        let mut stepped = wheel();
+        let per_step: Vec<Vec<usize>> = stepped.steps().take(100).collect();
+        assert_eq!(per_step.len(), 100);
+        assert_eq!(per_step[2], vec![3, 3]);
+        assert_eq!(per_step[16], vec![17]);
+
+        let mut ticked = wheel();
+        assert_eq!(per_step.concat(), ticked.tick(100));
+        assert_eq!(stepped.now(), ticked.now());
+    }
+
+    #[test]
+    fn test_steps_stop_early() {
+        let mut wheel = wheel();
+        let first = wheel.steps().find(|due| !due.is_empty());
+        assert_eq!(first, Some(vec![3, 3]));
+        assert_eq!(wheel.now(), 3);
+    }
+
+    #[test]
+    fn test_into_iter_sorted() {
+        let mut sorted = wheel();
+        sorted.advance(3);
+        let mut ticked = wheel();
+        let expected = ticked.tick(200);
+        assert_eq!(sorted.into_iter_sorted().collect::<Vec<_>>(), expected);
+
+        let mut prefix = wheel().into_iter_sorted();
+        assert_eq!(prefix.by_ref().take(3).collect::<Vec<_>>(), vec![3, 3, 17]);
+        assert_eq!(prefix.wheel.now(), 17);
+    }
+}
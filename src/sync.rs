@@ -0,0 +1,445 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard, TryLockError};
+
+use crate::{Entry, HierarchicalTimingWheel, ScheduleError, merge_due};
+
+/// Wheel behind a mutex, for scheduling from several threads while one thread ticks.
+pub struct SyncTimingWheel<T> {
+    wheel: Mutex<HierarchicalTimingWheel<T>>,
+}
+
+impl<T> SyncTimingWheel<T> {
+    pub fn new(wheel: HierarchicalTimingWheel<T>) -> SyncTimingWheel<T> {
+        Self {
+            wheel: Mutex::new(wheel),
+        }
+    }
+
+    /// Locks the wheel for several operations in a row.
+    pub fn lock(&self) -> MutexGuard<'_, HierarchicalTimingWheel<T>> {
+        self.wheel.lock().unwrap()
+    }
+
+    pub fn schedule(&self, delay_ticks: usize, timer: T) -> Result<(usize, usize), ScheduleError> {
+        self.lock().schedule(delay_ticks, timer)
+    }
+
+    pub fn tick(&self, steps: usize) -> Vec<T> {
+        self.lock().tick(steps)
+    }
+
+    /// Like `tick`, but returns `None` without advancing if the wheel is locked.
+    pub fn try_tick(&self, steps: usize) -> Option<Vec<T>> {
+        match self.wheel.try_lock() {
+            Ok(mut wheel) => Some(wheel.tick(steps)),
+            Err(TryLockError::WouldBlock) => None,
+            Err(TryLockError::Poisoned(err)) => panic!("{err}"),
+        }
+    }
+}
+
+type ShardFn<T> = Box<dyn Fn(&T) -> usize + Send + Sync>;
+
+/// Thread-safe set of independently locked wheels of the same geometry, so producers scheduling
+/// at the same time mostly take different locks. Unlike `ShardedTimingWheel`, a timer's shard
+/// does not depend on its delay: shards are picked round-robin, or by the function given to
+/// `with_shard_fn`. They always advance together: `tick` locks all of them in shard order before
+/// advancing any.
+pub struct PartitionedTimingWheel<T> {
+    shards: Vec<Mutex<HierarchicalTimingWheel<T>>>,
+    next_shard: AtomicUsize,
+    shard_fn: Option<ShardFn<T>>,
+}
+
+impl<T> PartitionedTimingWheel<T> {
+    pub fn new(
+        shards: usize,
+        levels: u32,
+        slot_capacity: usize,
+        slots_per_level: usize,
+    ) -> PartitionedTimingWheel<T> {
+        assert!(shards > 0, "a sharded wheel needs at least one shard");
+        Self {
+            shards: (0..shards)
+                .map(|_| {
+                    let wheel =
+                        HierarchicalTimingWheel::new(levels, slot_capacity, slots_per_level);
+                    Mutex::new(wheel)
+                })
+                .collect(),
+            next_shard: AtomicUsize::new(0),
+            shard_fn: None,
+        }
+    }
+
+    /// Like `new`, but schedules each timer on shard `shard_fn(&timer) % shards`, for callers that
+    /// already know how their timers should be grouped.
+    pub fn with_shard_fn(
+        shards: usize,
+        levels: u32,
+        slot_capacity: usize,
+        slots_per_level: usize,
+        shard_fn: impl Fn(&T) -> usize + Send + Sync + 'static,
+    ) -> PartitionedTimingWheel<T> {
+        let mut wheel = Self::new(shards, levels, slot_capacity, slots_per_level);
+        wheel.shard_fn = Some(Box::new(shard_fn));
+        wheel
+    }
+
+    pub fn schedule(&self, delay_ticks: usize, timer: T) -> Result<(usize, usize), ScheduleError> {
+        let shard = match &self.shard_fn {
+            Some(shard_fn) => shard_fn(&timer),
+            None => self.next_shard.fetch_add(1, Ordering::Relaxed),
+        } % self.shards.len();
+        self.shards[shard]
+            .lock()
+            .unwrap()
+            .schedule(delay_ticks, timer)
+    }
+
+    pub fn len(&self) -> usize {
+        self.locked().iter().map(|wheel| wheel.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.locked().iter().all(|wheel| wheel.is_empty())
+    }
+
+    /// Pending timers on each shard, in shard order.
+    pub fn shard_lens(&self) -> Vec<usize> {
+        self.locked().iter().map(|wheel| wheel.len()).collect()
+    }
+
+    /// Advances every shard and returns the fired timers ordered by fire tick, then by shard.
+    pub fn tick(&self, steps: usize) -> Vec<T> {
+        let outputs = self
+            .locked()
+            .iter_mut()
+            .map(|wheel| wheel.tick_timestamped(steps))
+            .collect();
+        merge_due(outputs)
+    }
+
+    fn locked(&self) -> Vec<MutexGuard<'_, HierarchicalTimingWheel<T>>> {
+        self.shards
+            .iter()
+            .map(|shard| shard.lock().unwrap())
+            .collect()
+    }
+}
+
+/// Thread-safe wheel whose slots are split into contiguous ranges, each behind its own lock and
+/// covering the same range of every ring, so producers scheduling into different slots do not
+/// contend. The clock is an atomic from which every ring's cursor follows, and it only moves
+/// while `tick` holds every lock, taken in range order. Fires the same timers at the same ticks
+/// as a `HierarchicalTimingWheel` of the same geometry.
+pub struct ShardedTimingWheel<T> {
+    shards: Vec<Mutex<SlotRange<T>>>,
+    levels: u32,
+    slots_per_level: usize,
+    current_tick: AtomicU64,
+    next_id: AtomicU64,
+}
+
+// Slots `first..first + width` of every ring, by level and then slot.
+struct SlotRange<T> {
+    first: usize,
+    width: usize,
+    slots: Vec<VecDeque<Entry<T>>>,
+}
+
+impl<T> SlotRange<T> {
+    fn slot(&mut self, level: usize, slot: usize) -> &mut VecDeque<Entry<T>> {
+        &mut self.slots[level * self.width + slot - self.first]
+    }
+}
+
+impl<T> ShardedTimingWheel<T> {
+    /// Panics unless `shards` is between 1 and `slots_per_level`.
+    pub fn new(
+        shards: usize,
+        levels: u32,
+        slot_capacity: usize,
+        slots_per_level: usize,
+    ) -> ShardedTimingWheel<T> {
+        assert!(
+            (1..=slots_per_level).contains(&shards),
+            "a sharded wheel needs between 1 and slots_per_level shards"
+        );
+        Self {
+            shards: (0..shards)
+                .map(|shard| {
+                    let first = (shard * slots_per_level).div_ceil(shards);
+                    let width = ((shard + 1) * slots_per_level).div_ceil(shards) - first;
+                    let slots = (0..levels as usize * width)
+                        .map(|_| VecDeque::with_capacity(slot_capacity))
+                        .collect();
+                    Mutex::new(SlotRange {
+                        first,
+                        width,
+                        slots,
+                    })
+                })
+                .collect(),
+            levels,
+            slots_per_level,
+            current_tick: AtomicU64::new(0),
+            next_id: AtomicU64::new(0),
+        }
+    }
+
+    pub fn now(&self) -> u64 {
+        self.current_tick.load(Ordering::Acquire)
+    }
+
+    /// Schedules `timer` in the slot `HierarchicalTimingWheel::schedule` would pick, taking only
+    /// the lock of that slot's range.
+    pub fn schedule(&self, delay_ticks: usize, timer: T) -> Result<(usize, usize), ScheduleError> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        loop {
+            let now = self.now();
+            let (level, slot, remaining) = self
+                .place(delay_ticks, now)
+                .ok_or(ScheduleError::DelayTooLarge)?;
+            let mut range = self.shards[self.shard_of(slot)].lock().unwrap();
+            // a tick that started meanwhile may have moved the slot, so place the timer again
+            if self.now() != now {
+                continue;
+            }
+            range.slot(level, slot).push_back(Entry {
+                remaining,
+                id,
+                requested: now + delay_ticks.max(1) as u64,
+                timer,
+            });
+            return Ok((level, slot));
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.shard_lens().iter().sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Pending timers in each slot range, in range order.
+    pub fn shard_lens(&self) -> Vec<usize> {
+        self.locked()
+            .iter()
+            .map(|range| range.slots.iter().map(VecDeque::len).sum())
+            .collect()
+    }
+
+    /// Advances the wheel `steps` ticks with every range locked and returns the fired timers,
+    /// ordered by fire tick and then by the order they were scheduled in.
+    pub fn tick(&self, steps: usize) -> Vec<T> {
+        let mut ranges = self.locked();
+        let mut fired = Vec::new();
+        for _ in 0..steps {
+            let now = self.now() + 1;
+            self.current_tick.store(now, Ordering::Release);
+            let mut due = Vec::new();
+            let mut graduated = Vec::new();
+            for level in 0..self.levels {
+                let span = self.slots_per_level.pow(level) as u64;
+                if !now.is_multiple_of(span) {
+                    break;
+                }
+                let slot = (now / span % self.slots_per_level as u64) as usize;
+                let range = &mut ranges[self.shard_of(slot)];
+                let entries = range.slot(level as usize, slot).drain(..);
+                match level {
+                    0 => due.extend(entries),
+                    _ => graduated.extend(entries),
+                }
+            }
+            for mut entry in graduated {
+                if entry.remaining == 0 {
+                    due.push(entry);
+                    continue;
+                }
+                let (level, slot, remaining) = self
+                    .place(entry.remaining, now)
+                    .expect("a graduated remainder always fits below its ring");
+                entry.remaining = remaining;
+                ranges[self.shard_of(slot)]
+                    .slot(level, slot)
+                    .push_back(entry);
+            }
+            due.sort_unstable_by_key(|entry| entry.id);
+            fired.extend(due.into_iter().map(|entry| entry.timer));
+        }
+        fired
+    }
+
+    // Level, slot and remainder for a timer due `delay_ticks` after `now`, as `Ring::place`
+    // computes them.
+    fn place(&self, delay_ticks: usize, now: u64) -> Option<(usize, usize, usize)> {
+        let delay_ticks = delay_ticks.max(1);
+        (0..self.levels).find_map(|level| {
+            let span = self.slots_per_level.pow(level);
+            if delay_ticks < span || delay_ticks >= span * self.slots_per_level {
+                return None;
+            }
+            let cursor = (now / span as u64 % self.slots_per_level as u64) as usize;
+            let slot = (cursor + delay_ticks / span) % self.slots_per_level;
+            Some((level as usize, slot, delay_ticks % span))
+        })
+    }
+
+    fn shard_of(&self, slot: usize) -> usize {
+        slot * self.shards.len() / self.slots_per_level
+    }
+
+    fn locked(&self) -> Vec<MutexGuard<'_, SlotRange<T>>> {
+        self.shards
+            .iter()
+            .map(|shard| shard.lock().unwrap())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::mpsc;
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn test_try_tick_under_contention() {
+        let wheel = Arc::new(SyncTimingWheel::new(HierarchicalTimingWheel::new(
+            2, 16, 10,
+        )));
+        wheel.schedule(1, "A").unwrap();
+
+        let (locked_tx, locked_rx) = mpsc::channel();
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+        let scheduler = {
+            let wheel = Arc::clone(&wheel);
+            thread::spawn(move || {
+                let mut guard = wheel.lock();
+                locked_tx.send(()).unwrap();
+                release_rx.recv().unwrap();
+                guard.schedule(1, "B").unwrap();
+            })
+        };
+
+        locked_rx.recv().unwrap();
+        assert_eq!(wheel.try_tick(1), None);
+        release_tx.send(()).unwrap();
+        scheduler.join().unwrap();
+
+        assert_eq!(wheel.lock().now(), 0);
+        assert_eq!(wheel.try_tick(1), Some(vec!["A", "B"]));
+    }
+
+    #[test]
+    fn test_sharded_wheel() {
+        let wheel = Arc::new(ShardedTimingWheel::new(4, 3, 16, 10));
+        let producers: Vec<_> = (0..8)
+            .map(|producer| {
+                let wheel = Arc::clone(&wheel);
+                thread::spawn(move || {
+                    for delay in 1..=50 {
+                        wheel.schedule(delay, (delay, producer)).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for producer in producers {
+            producer.join().unwrap();
+        }
+        assert_eq!(wheel.len(), 400);
+
+        let mut fired = wheel.tick(50);
+        assert_eq!(fired.len(), 400);
+        assert!(fired.is_sorted_by_key(|(delay, _)| *delay));
+        fired.sort();
+        fired.dedup();
+        assert_eq!(fired.len(), 400);
+        assert!(wheel.is_empty());
+    }
+
+    #[test]
+    fn test_partitioned_wheel_with_shard_fn() {
+        let wheel =
+            PartitionedTimingWheel::with_shard_fn(3, 2, 16, 10, |(tenant, _): &(usize, &str)| {
+                *tenant
+            });
+        for (delay, timer) in [(2, (4, "a")), (1, (0, "b")), (5, (1, "c")), (2, (1, "d"))] {
+            wheel.schedule(delay, timer).unwrap();
+        }
+        assert_eq!(wheel.shard_lens(), vec![1, 3, 0]);
+
+        assert_eq!(wheel.tick(2), vec![(0, "b"), (4, "a"), (1, "d")]);
+        assert_eq!(wheel.shard_lens(), vec![0, 1, 0]);
+        assert_eq!(wheel.tick(3), vec![(1, "c")]);
+    }
+
+    #[test]
+    fn test_sharded_wheel_matches_single_wheel() {
+        let sharded = ShardedTimingWheel::new(3, 3, 16, 10);
+        let mut single = HierarchicalTimingWheel::new(3, 16, 10);
+        // the 3 ranges cover slots 0 to 3, 4 to 6 and 7 to 9 of every ring
+        for delay in [1, 5, 9, 25, 74] {
+            sharded.schedule(delay, delay).unwrap();
+        }
+        assert_eq!(sharded.shard_lens(), vec![2, 1, 2]);
+        assert!(matches!(
+            sharded.schedule(1000, 0),
+            Err(ScheduleError::DelayTooLarge)
+        ));
+
+        for delay in [1, 5, 9, 25, 74] {
+            single.schedule(delay, delay).unwrap();
+        }
+        for step in 0..400 {
+            if step % 7 == 0 {
+                let delay = step * 13 % 999 + 1;
+                assert_eq!(
+                    sharded.schedule(delay, delay).unwrap(),
+                    single.schedule(delay, delay).unwrap()
+                );
+            }
+            let mut fired = sharded.tick(1);
+            let mut expected = single.tick(1);
+            fired.sort();
+            expected.sort();
+            assert_eq!(fired, expected);
+        }
+        assert_eq!(sharded.len(), single.len());
+        assert_eq!(sharded.now(), 400);
+    }
+
+    #[test]
+    fn test_sharded_wheel_schedules_while_ticking() {
+        let wheel = Arc::new(ShardedTimingWheel::new(5, 2, 16, 10));
+        let producers: Vec<_> = (0..4)
+            .map(|producer| {
+                let wheel = Arc::clone(&wheel);
+                thread::spawn(move || {
+                    for i in 0..500 {
+                        wheel.schedule(i % 99 + 1, producer * 500 + i).unwrap();
+                    }
+                })
+            })
+            .collect();
+        let mut fired = Vec::new();
+        while producers.iter().any(|producer| !producer.is_finished()) {
+            fired.extend(wheel.tick(1));
+        }
+        for producer in producers {
+            producer.join().unwrap();
+        }
+        fired.extend(wheel.tick(100));
+
+        assert!(wheel.is_empty());
+        fired.sort();
+        assert_eq!(fired, (0..2000).collect::<Vec<_>>());
+    }
+}
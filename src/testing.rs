@@ -0,0 +1,271 @@
+use std::fmt::Debug;
+
+use crate::{HierarchicalTimingWheel, filter_bits};
+
+impl<T> HierarchicalTimingWheel<T> {
+    /// Panics, saying which one broke, if any of the wheel's internal invariants does not hold.
+    pub fn check_invariants(&self) {
+        let mut ids = std::collections::HashSet::new();
+        for (level, ring) in self.rings.iter().enumerate() {
+            let slots = ring.slots.len();
+            assert!(
+                ring.cursor < slots,
+                "level {level} cursor {} out of range",
+                ring.cursor
+            );
+            let expected = (self.current_tick / ring.span() as u64 % slots as u64) as usize;
+            assert_eq!(
+                ring.cursor, expected,
+                "level {level} cursor does not match tick {}",
+                self.current_tick
+            );
+            for (slot, entries) in ring.slots.iter().enumerate() {
+                for entry in entries {
+                    assert!(
+                        entry.remaining < ring.span(),
+                        "level {level} slot {slot} holds remainder {} beyond the span {}",
+                        entry.remaining,
+                        ring.span()
+                    );
+                    let bits = filter_bits(entry.id);
+                    assert_eq!(
+                        ring.filters[slot] & bits,
+                        bits,
+                        "level {level} slot {slot} filter misses timer {}",
+                        entry.id
+                    );
+                    assert!(ids.insert(entry.id), "timer {} stored twice", entry.id);
+                    #[cfg(feature = "fast-cancel")]
+                    assert_eq!(
+                        ring.index.get(&entry.id),
+                        Some(&slot),
+                        "level {level} index misplaces timer {}",
+                        entry.id
+                    );
+                    assert!(
+                        entry.id < self.next_id,
+                        "timer {} was never issued",
+                        entry.id
+                    );
+                }
+            }
+        }
+        if self.max_cascade_per_tick.is_some() {
+            for level in 1..self.rings.len() {
+                let ring = &self.rings[level];
+                let deferred = &ring.slots[ring.cursor];
+                let position = self.position(level);
+                assert!(
+                    deferred
+                        .iter()
+                        .zip(deferred.iter().skip(1))
+                        .all(|(a, b)| (a.remaining, a.id) < (b.remaining, b.id)),
+                    "level {level} deferred slot is out of order"
+                );
+                if let Some(entry) = deferred.front() {
+                    assert!(
+                        entry.remaining > position,
+                        "level {level} deferred timer {} is overdue",
+                        entry.id
+                    );
+                }
+            }
+        }
+        assert_eq!(
+            self.pending,
+            ids.len(),
+            "len counter does not match the stored timers"
+        );
+        assert_eq!(
+            self.explicit_ids.len(),
+            self.explicit_by_internal.len(),
+            "explicit id tables differ in size"
+        );
+        for (explicit, internal) in &self.explicit_ids {
+            assert_eq!(
+                self.explicit_by_internal.get(internal),
+                Some(explicit),
+                "explicit id {explicit:?} is not mapped back"
+            );
+            assert!(
+                ids.contains(internal),
+                "explicit id {explicit:?} points at no timer"
+            );
+        }
+    }
+}
+
+/// Drives a wheel tick by tick and records when each timer fired, with assertions that explain
+/// what actually happened when they fail.
+pub struct SimulationHarness<T> {
+    wheel: HierarchicalTimingWheel<T>,
+    tick: u64,
+}
+
+impl<T> SimulationHarness<T> {
+    pub fn new(wheel: HierarchicalTimingWheel<T>) -> SimulationHarness<T> {
+        let tick = wheel.now();
+        Self { wheel, tick }
+    }
+
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    pub fn wheel(&self) -> &HierarchicalTimingWheel<T> {
+        &self.wheel
+    }
+
+    pub fn schedule(&mut self, delay_ticks: usize, timer: T) -> (usize, usize) {
+        match self.wheel.schedule(delay_ticks, timer) {
+            Ok(placement) => {
+                self.wheel.check_invariants();
+                placement
+            }
+            Err(err) => panic!(
+                "failed to schedule timer with delay {delay_ticks} at tick {}: {err:?} \
+                 (max delay is {})",
+                self.tick,
+                self.wheel.max_delay()
+            ),
+        }
+    }
+
+    /// Advances `steps` ticks and returns the fired timers tagged with the tick they fired at.
+    pub fn advance(&mut self, steps: usize) -> Vec<(u64, T)> {
+        let fired = self.wheel.tick_timestamped(steps);
+        self.tick += steps as u64;
+        self.wheel.check_invariants();
+        fired
+    }
+
+    pub fn assert_next_fires_at(&self, tick: u64) {
+        match self.wheel.next_expiry() {
+            Some(expiry) => assert_eq!(
+                self.tick + expiry as u64,
+                tick,
+                "expected the next timer to fire at tick {tick}, but it fires at tick {} \
+                 ({expiry} ticks after the current tick {})",
+                self.tick + expiry as u64,
+                self.tick
+            ),
+            None => panic!(
+                "expected the next timer to fire at tick {tick}, but no timers are pending \
+                 at tick {}",
+                self.tick
+            ),
+        }
+    }
+
+    pub fn assert_empty(&self) {
+        assert!(
+            self.wheel.is_empty(),
+            "expected no pending timers at tick {}, but {} are pending (next fires in {:?} ticks)",
+            self.tick,
+            self.wheel.len(),
+            self.wheel.next_expiry()
+        );
+    }
+}
+
+impl<T: PartialEq + Debug> SimulationHarness<T> {
+    /// Advances up to the last expected tick and asserts that exactly the expected timers fired,
+    /// at the expected ticks and in the expected order.
+    pub fn assert_fires_in_order(&mut self, expected: Vec<(u64, T)>) {
+        let until = expected
+            .iter()
+            .map(|(tick, _)| *tick)
+            .max()
+            .unwrap_or(self.tick);
+        assert!(
+            until >= self.tick,
+            "expected firings up to tick {until}, but the harness is already at tick {}",
+            self.tick
+        );
+        let fired = self.advance((until - self.tick) as usize);
+        assert_eq!(
+            fired, expected,
+            "timers fired as (tick, timer) = {fired:?}, expected {expected:?}"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hierarchical;
+
+    #[test]
+    fn test_harness() {
+        let mut harness = SimulationHarness::new(hierarchical(2, 16, 10));
+        harness.schedule(3, "A");
+        harness.schedule(15, "B");
+        harness.schedule(3, "C");
+
+        harness.assert_next_fires_at(3);
+        harness.assert_fires_in_order(vec![(3, "A"), (3, "C")]);
+        harness.assert_next_fires_at(15);
+        assert_eq!(harness.advance(20), vec![(15, "B")]);
+        assert_eq!(harness.tick(), 23);
+        harness.assert_empty();
+    }
+
+    #[test]
+    #[should_panic(expected = "expected no pending timers at tick 0, but 1 are pending")]
+    fn test_assert_empty_message() {
+        let mut harness = SimulationHarness::new(hierarchical(1, 16, 10));
+        harness.schedule(1, ());
+        harness.assert_empty();
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "timers fired as (tick, timer) = [(2, \"A\")], expected [(3, \"A\")]"
+    )]
+    fn test_assert_fires_in_order_message() {
+        let mut harness = SimulationHarness::new(hierarchical(1, 16, 10));
+        harness.schedule(2, "A");
+        harness.assert_fires_in_order(vec![(3, "A")]);
+    }
+
+    #[test]
+    fn test_invariants_hold_under_random_operations() {
+        use crate::TimerId;
+
+        let mut wheel = hierarchical(3, 4, 8);
+        let mut handles = Vec::new();
+        // xorshift, so failures reproduce
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let mut next = |bound: usize| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % bound as u64) as usize
+        };
+        for op in 0..5000 {
+            match next(8) {
+                0..=2 => handles.push(wheel.schedule_with_handle(next(512), op).unwrap()),
+                3 => {
+                    let _ = wheel.schedule_with_id(TimerId(next(64) as u64), next(512), op);
+                }
+                4 if !handles.is_empty() => {
+                    let handle = handles.swap_remove(next(handles.len()));
+                    wheel.cancel(handle);
+                }
+                5 if !handles.is_empty() => {
+                    let handle = handles[next(handles.len())];
+                    let _ = wheel.extend_deadline(handle, next(64));
+                }
+                6 => {
+                    wheel.cancel_id(TimerId(next(64) as u64));
+                }
+                _ => {
+                    wheel.tick(next(40));
+                }
+            }
+            wheel.check_invariants();
+        }
+        wheel.rebalance();
+        wheel.check_invariants();
+    }
+}
@@ -0,0 +1,128 @@
+use crate::{HierarchicalTimingWheel, Ring};
+
+/// Advanced: mutable access to a single ring, obtained through `ring_mut`.
+///
+/// The view exposes the ring's cursor read-only and lets timers be inspected, edited, removed or
+/// moved between slots of the same ring. Moving a timer changes when it fires, but every
+/// operation keeps the stored remainders and cursors valid, so the cascade keeps working.
+pub struct RingView<'a, T> {
+    wheel: &'a mut HierarchicalTimingWheel<T>,
+    level: usize,
+}
+
+impl<T> HierarchicalTimingWheel<T> {
+    /// Advanced: see `RingView`. Returns `None` if `level` does not exist.
+    pub fn ring_mut(&mut self, level: usize) -> Option<RingView<'_, T>> {
+        if level >= self.rings.len() {
+            return None;
+        }
+        Some(RingView { wheel: self, level })
+    }
+}
+
+impl<T> RingView<'_, T> {
+    fn ring(&self) -> &Ring<T> {
+        &self.wheel.rings[self.level]
+    }
+
+    fn ring_mut(&mut self) -> &mut Ring<T> {
+        &mut self.wheel.rings[self.level]
+    }
+
+    pub fn level(&self) -> usize {
+        self.level
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.ring().cursor
+    }
+
+    pub fn slot_count(&self) -> usize {
+        self.ring().slots.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.ring().slots.iter().map(|slot| slot.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ring().slots.iter().all(|slot| slot.is_empty())
+    }
+
+    /// `(remaining, timer)` pairs of one slot, in firing order.
+    pub fn slot(&self, slot: usize) -> Option<Vec<(usize, &T)>> {
+        let slot = self.ring().slots.get(slot)?;
+        Some(
+            slot.iter()
+                .map(|entry| (entry.remaining, &entry.timer))
+                .collect(),
+        )
+    }
+
+    pub fn timers_mut(&mut self, slot: usize) -> Option<impl Iterator<Item = &mut T>> {
+        let slot = self.ring_mut().slots.get_mut(slot)?;
+        Some(slot.iter_mut().map(|entry| &mut entry.timer))
+    }
+
+    /// Removes and returns every `(remaining, timer)` pair of one slot.
+    pub fn take_slot(&mut self, slot: usize) -> Option<Vec<(usize, T)>> {
+        if slot >= self.slot_count() {
+            return None;
+        }
+        let entries: Vec<_> = self.ring_mut().drain_slot(slot).collect();
+        Some(
+            entries
+                .into_iter()
+                .map(|entry| (entry.remaining, self.wheel.release(entry)))
+                .collect(),
+        )
+    }
+
+    /// Moves the timer at `index` of slot `from` to the back of slot `to`, keeping its remainder.
+    /// Returns `false` if either slot or the index does not exist.
+    pub fn move_timer(&mut self, from: usize, index: usize, to: usize) -> bool {
+        if to >= self.ring().slots.len() {
+            return false;
+        }
+        let Some(entry) = self
+            .ring_mut()
+            .slots
+            .get_mut(from)
+            .and_then(|slot| slot.remove(index))
+        else {
+            return false;
+        };
+        self.ring_mut().push(to, entry);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::HierarchicalTimingWheel;
+
+    #[test]
+    fn test_ring_mut() {
+        let mut wheel = HierarchicalTimingWheel::new(2, 16, 10);
+        wheel.schedule(3, "A").unwrap();
+        wheel.schedule(25, "B").unwrap();
+        wheel.schedule(4, "C").unwrap();
+        assert!(wheel.ring_mut(2).is_none());
+
+        let mut ring = wheel.ring_mut(0).unwrap();
+        assert_eq!((ring.level(), ring.cursor(), ring.len()), (0, 0, 2));
+        assert!(ring.move_timer(3, 0, 1));
+        assert!(!ring.move_timer(3, 0, 1));
+        assert_eq!(ring.take_slot(4), Some(vec![(0, "C")]));
+        for timer in ring.timers_mut(1).unwrap() {
+            *timer = "a";
+        }
+
+        let ring = wheel.ring_mut(1).unwrap();
+        assert_eq!(ring.slot(2), Some(vec![(5, &"B")]));
+
+        assert_eq!(wheel.tick(1), vec!["a"]);
+        assert!(wheel.tick(23).is_empty());
+        assert_eq!(wheel.tick(1), vec!["B"]);
+    }
+}
@@ -0,0 +1,10 @@
+fn main() {
+    let mut a = timing_wheel::hierarchical::<&str>(2, 16, 10);
+    let mut b = timing_wheel::hierarchical::<&str>(2, 16, 10);
+    a.with_brand(|mut a| {
+        let handle = a.schedule(5, "A").unwrap();
+        b.with_brand(|mut b| {
+            b.cancel(handle);
+        });
+    });
+}
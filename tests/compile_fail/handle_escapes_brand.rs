@@ -0,0 +1,7 @@
+fn main() {
+    let mut wheel = timing_wheel::hierarchical(2, 16, 10);
+    let handle = wheel.with_brand(|mut wheel| wheel.schedule(5, "A").unwrap());
+    wheel.with_brand(|mut wheel| {
+        wheel.cancel(handle);
+    });
+}
@@ -0,0 +1,33 @@
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+const THREADS: usize = 16;
+const TIMERS_PER_THREAD: usize = 1000;
+
+#[test]
+fn concurrent_schedule_then_drain_test() {
+    let wheel = Arc::new(Mutex::new(timing_wheel::hierarchical(3, 16, 10)));
+    let max_delay = wheel.lock().unwrap().max_delay();
+
+    let producers: Vec<_> = (0..THREADS)
+        .map(|thread| {
+            let wheel = Arc::clone(&wheel);
+            thread::spawn(move || {
+                for i in 0..TIMERS_PER_THREAD {
+                    let delay = i % max_delay + 1;
+                    wheel.lock().unwrap().schedule(delay, (thread, i)).unwrap();
+                }
+            })
+        })
+        .collect();
+    for producer in producers {
+        producer.join().unwrap();
+    }
+
+    let fired = wheel.lock().unwrap().tick(max_delay);
+    assert_eq!(fired.len(), THREADS * TIMERS_PER_THREAD);
+    let unique: HashSet<_> = fired.into_iter().collect();
+    assert_eq!(unique.len(), THREADS * TIMERS_PER_THREAD);
+    assert!(wheel.lock().unwrap().is_empty());
+}